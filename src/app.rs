@@ -1,166 +1,340 @@
-//! clap App for command cli
-use clap::{Command, Arg, ArgAction};
+//! clap App for command cli, and sub commands are variants of `Cli`
+use crate::completion::template_candidates;
+use crate::output::OutputFormat;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCandidates;
+use std::path::PathBuf;
+use tgm::format::FileFormat;
+use tgm::VERSION;
 
-const VERSION: &str = "0.10.0";
+/// Template generator manager: https://github.com/linux-china/tgm
+#[derive(Parser, Debug)]
+#[command(name = "tgm", version = VERSION)]
+pub struct App {
+    /// output format of list, search, info and config
+    #[arg(long, value_enum, global = true)]
+    pub output: Option<OutputFormat>,
+    /// settings file, such as ~/.tgm/settings.json
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Cli>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Cli {
+    /// List templates
+    List {
+        /// remotes template
+        #[arg(long)]
+        remote: bool,
+    },
+    /// Search templates by name, description and tags
+    Search {
+        /// search text
+        query: Option<String>,
+        /// filter by tag, such as --tag java --tag spring
+        #[arg(long)]
+        tag: Vec<String>,
+        /// search local templates only
+        #[arg(long)]
+        local: bool,
+    },
+    /// Show/config global variables
+    Config(ConfigArgs),
+    /// Generate LICENSE file by SPDX license identifier
+    License(LicenseArgs),
+    /// Generate shell completion for bash, zsh, fish, PowerShell & elvish
+    Complete(CompleteArgs),
+    /// Add template
+    Add {
+        /// template name
+        #[arg(long)]
+        name: String,
+        /// git repository url
+        #[arg(long)]
+        repo: String,
+        /// template description
+        #[arg(long)]
+        desc: String,
+        /// template tag, such as java
+        #[arg(long)]
+        tag: Vec<String>,
+    },
+    /// Remove template from local settings
+    Remove {
+        /// template name
+        #[arg(add = ArgValueCandidates::new(template_candidates))]
+        name: String,
+    },
+    /// Import template from repository's template.json
+    Import {
+        /// github's repository name or absolute url
+        name: String,
+    },
+    /// Manage template registry index
+    Registry {
+        #[command(subcommand)]
+        command: Option<RegistryCommand>,
+    },
+    /// Check template.json and template files for template authors
+    Lint {
+        /// template directory, current directory by default
+        dir: Option<PathBuf>,
+    },
+    /// Render template's test cases and compare with snapshots
+    Test {
+        /// template directory, current directory by default
+        dir: Option<PathBuf>,
+        /// update snapshots with rendered output
+        #[arg(long)]
+        update: bool,
+    },
+    /// Turn existing project into template with variable placeholders
+    InitTemplate {
+        /// project directory
+        dir: PathBuf,
+        /// literal value to parameterize, such as --var groupId=com.acme.demo
+        #[arg(long, required = true)]
+        var: Vec<String>,
+        /// apply changes without confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Show template's variables, files and hooks
+    Info {
+        /// template name, template.json path or url
+        #[arg(add = ArgValueCandidates::new(template_candidates))]
+        name: String,
+        /// fetch template.json again instead of the cached one
+        #[arg(long)]
+        refresh: bool,
+        /// deprecated alias of `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+    /// Create app from template
+    Create {
+        /// template name, and template is picked interactively if absent
+        #[arg(add = ArgValueCandidates::new(template_candidates))]
+        name: Option<String>,
+        /// App's directory, and prompted if absent
+        dir: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    /// edit global variables
+    #[arg(long)]
+    pub edit: bool,
+    /// convert settings file to json, yaml or toml
+    #[arg(long, value_enum)]
+    pub convert: Option<FileFormat>,
+    /// show settings file of each value: system, user or project settings
+    #[arg(long)]
+    pub show_origin: bool,
+    #[command(subcommand)]
+    pub command: Option<ConfigCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Set global variable or settings value
+    Set {
+        /// variable name, or central, github_api, github_token, registries and trusted_projects
+        name: String,
+        /// value, and registries and trusted_projects are separated by comma
+        value: String,
+        /// variable description
+        #[arg(long)]
+        desc: Option<String>,
+        /// secret variable, and value is masked in output
+        #[arg(long)]
+        secret: bool,
+    },
+    /// Get global variable or settings value
+    Get {
+        /// variable name, or central, github_api, github_token, registries and trusted_projects
+        name: String,
+        /// print secret value and github_token instead of mask
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Remove global variable or settings value
+    Unset {
+        /// variable name, or central, github_api, github_token, registries and trusted_projects
+        name: String,
+    },
+    /// List global variables and settings values
+    List,
+}
+
+#[derive(Args, Debug)]
+pub struct CompleteArgs {
+    /// Zsh completion
+    #[arg(long)]
+    pub zsh: bool,
+    /// Install tgm plugin for Oh My Zsh
+    #[arg(long = "oh_my_zsh")]
+    pub oh_my_zsh: bool,
+    /// Uninstall tgm plugin for Oh My Zsh
+    #[arg(long, requires = "oh_my_zsh")]
+    pub uninstall: bool,
+    /// Bash completion
+    #[arg(long)]
+    pub bash: bool,
+    /// Fish completion
+    #[arg(long)]
+    pub fish: bool,
+    /// PowerShell completion
+    #[arg(long)]
+    pub powershell: bool,
+    /// Elvish completion
+    #[arg(long)]
+    pub elvish: bool,
+    /// Dynamic completion, with template names from settings and cached remote templates
+    #[arg(long)]
+    pub dynamic: bool,
+}
+
+impl CompleteArgs {
+    /// shell of completion script, and None if no shell flag present
+    pub fn shell(&self) -> Option<&'static str> {
+        let flags = [self.bash, self.zsh, self.fish, self.powershell, self.elvish];
+        crate::completion::SHELLS
+            .iter()
+            .zip(flags)
+            .find_map(|(shell, flag)| if flag { Some(*shell) } else { None })
+    }
+}
+
+/// license names before SPDX identifiers, such as `--kind apache2`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum LicenseKind {
+    /// Apache License 2.0
+    Apache2,
+    /// MIT License
+    Mit,
+    /// ISC License
+    Isc,
+    /// GNU GPLv3
+    Gplv3,
+    /// GNU LGPLv3
+    Lgplv3,
+    /// Mozilla Public License 2.0
+    Mozilla2,
+}
+
+impl LicenseKind {
+    /// legacy license name, which is resolved to SPDX identifier by license lookup
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct LicenseArgs {
+    /// SPDX license identifier or expression, such as MIT, BSD-3-Clause or 'MIT OR Apache-2.0'
+    pub id: Option<String>,
+    /// License name before SPDX identifiers
+    #[arg(long, value_enum, conflicts_with = "id")]
+    pub kind: Option<LicenseKind>,
+    /// List available licenses
+    #[arg(long)]
+    pub list: bool,
+    /// License file path, or directory for multiple licenses, current directory by default
+    #[arg(long)]
+    pub dest: Option<PathBuf>,
+    /// Overwrite existing license files
+    #[arg(long)]
+    pub force: bool,
+    /// Generate NOTICE file, from NOTICE.txt in tgm config directory if present
+    #[arg(long)]
+    pub notice: bool,
+    /// Generate AUTHORS file from git commit authors
+    #[arg(long)]
+    pub authors: bool,
+    /// Insert or update license headers in source files
+    #[arg(long)]
+    pub headers: bool,
+    /// Use full license notice as header instead of SPDX identifier
+    #[arg(long, requires = "headers")]
+    pub full: bool,
+    /// Check license headers without changing files, and exit with 1 if absent or outdated
+    #[arg(long, requires = "headers")]
+    pub check: bool,
+    /// Source directory, current directory by default
+    #[arg(long, requires = "headers")]
+    pub dir: Option<PathBuf>,
+    /// Project name, current directory name by default
+    #[arg(long)]
+    pub project: Option<String>,
+    /// Copyright holder, author_name global variable by default
+    #[arg(long)]
+    pub author: Option<String>,
+}
+
+impl LicenseArgs {
+    /// SPDX license expression, or legacy license name from `--kind`
+    pub fn license_id(&self) -> Option<String> {
+        self.id.clone().or_else(|| self.kind.map(LicenseKind::name))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RegistryCommand {
+    /// Build registry index from a directory of templates
+    Build {
+        /// directory with template sub directories
+        dir: String,
+        /// index file, such as index.json or index.toml, and index is printed if absent
+        #[arg(long)]
+        dest: Option<String>,
+    },
+    /// Validate registry index
+    Check {
+        /// index file path or url
+        source: String,
+    },
+    /// Add registry index to settings
+    Add {
+        /// index file path or url
+        source: String,
+    },
+    /// Remove registry index from settings
+    Remove {
+        /// index file path or url
+        source: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
 
-pub fn build_app() -> Command {
-    let add_command = Command::new("add")
-        .about("Add template")
-        .arg(
-            Arg::new("name")
-                .long("name") // allow --name
-                .num_args(1)
-                .help("template name")
-                .required(true),
-        )
-        .arg(
-            Arg::new("repo")
-                .long("repo") // allow --name
-                .num_args(1)
-                .help("git repository url")
-                .required(true),
-        )
-        .arg(
-            Arg::new("desc")
-                .long("desc") // allow --name
-                .num_args(1)
-                .help("template description")
-                .required(true),
-        );
-    let create_command = Command::new("create")
-        .about("Create app from template")
-        .arg(
-            Arg::new("name")
-                //.long("name") // allow --name
-                .num_args(1)
-                .help("template name")
-                .required(true)
-                .index(1),
-        )
-        .arg(
-            Arg::new("dir")
-                //.long("dir") // allow --name
-                .num_args(1)
-                .help("App's directory")
-                .required(true)
-                .index(2),
-        );
-    let remove_command = Command::new("remove")
-        .about("Remove template from local settings")
-        .arg(
-            Arg::new("name")
-                .num_args(1)
-                .help("template name")
-                .required(true),
-        );
-    let list_command = Command::new("list").about("List templates").arg(
-        Arg::new("remote")
-            .long("remote")
-            .num_args(1)
-            .help("remotes template")
-            .required(false),
-    );
-    let config_command = Command::new("config")
-        .about("Show/config global variables")
-        .arg(
-            Arg::new("edit")
-                .long("edit")
-                .action(ArgAction::SetTrue)
-                .help("edit global variables ")
-                .required(false),
-        );
-    let complete_command = Command::new("complete")
-        .about("Generate shell completion for zsh & bash")
-        .arg(
-            Arg::new("zsh")
-                .long("zsh")
-                .action(ArgAction::SetTrue)
-                .help("Zsh completion")
-                .required(false),
-        )
-        .arg(
-            Arg::new("oh_my_zsh")
-                .long("oh_my_zsh")
-                .action(ArgAction::SetTrue)
-                .help("Oh My Zsh")
-                .required(false),
-        )
-        .arg(
-            Arg::new("bash")
-                .long("bash")
-                .action(ArgAction::SetTrue)
-                .help("Bash completion")
-                .required(false),
-        );
-    let import_command = Command::new("import")
-        .about("Import template from repository's template.json")
-        .arg(
-            Arg::new("name")
-                .num_args(1)
-                .help("github's repository name or absolute url")
-                .required(true),
-        );
-    let license_command = Command::new("license")
-        .about("Generate LICENSE file")
-        .arg(
-            Arg::new("apache2")
-                .long("apache2")
-                .action(ArgAction::SetTrue)
-                .help("Apache License 2.0")
-                .required(false),
-        )
-        .arg(
-            Arg::new("mit")
-                .long("mit")
-                .action(ArgAction::SetTrue)
-                .help("MIT License")
-                .required(false),
-        )
-        .arg(
-            Arg::new("isc")
-                .long("isc")
-                .action(ArgAction::SetTrue)
-                .help("ISC License")
-                .required(false),
-        )
-        .arg(
-            Arg::new("gplv3")
-                .long("gplv3")
-                .action(ArgAction::SetTrue)
-                .help("GNU GPLv3 ")
-                .required(false),
-        )
-        .arg(
-            Arg::new("lgplv3")
-                .long("lgplv3")
-                .action(ArgAction::SetTrue)
-                .help("GNU LGPLv3")
-                .required(false),
-        )
-        .arg(
-            Arg::new("mozilla2")
-                .long("mozilla2")
-                .action(ArgAction::SetTrue)
-                .help("Mozilla Public License 2.0")
-                .required(false),
-        )
-        .arg(
-            Arg::new("author")
-                .long("author")
-                .num_args(1)
-                .help("Author name")
-                .required(true),
-        );
-    // init Clap
-    Command::new("tgm")
-        .version(VERSION)
-        .about("Template generator manager: https://github.com/linux-china/tgm")
-        .subcommand(list_command)
-        .subcommand(config_command)
-        .subcommand(license_command)
-        .subcommand(complete_command)
-        .subcommand(add_command)
-        .subcommand(remove_command)
-        .subcommand(import_command)
-        .subcommand(create_command)
+    #[test]
+    fn test_cli() {
+        App::command().debug_assert();
+        let app = App::parse_from(["tgm", "list", "--remote", "--output", "json"]);
+        let command = app.command.unwrap();
+        assert!(matches!(command, Cli::List { remote: true, .. }));
+        assert_eq!(app.output, Some(OutputFormat::Json));
+        let app = App::parse_from(["tgm", "--output", "yaml", "config", "list"]);
+        assert_eq!(app.output, Some(OutputFormat::Yaml));
+        let app = App::parse_from(["tgm", "info", "demo", "--output", "json"]);
+        assert_eq!(app.output, Some(OutputFormat::Json));
+        let app = App::parse_from(["tgm", "info", "demo", "--json"]);
+        assert!(matches!(app.command, Some(Cli::Info { json: true, .. })));
+        let app = App::parse_from(["tgm", "license", "--kind", "apache2", "--force"]);
+        let Some(Cli::License(args)) = app.command else {
+            panic!("license command expected");
+        };
+        assert_eq!(args.license_id().unwrap(), "apache2");
+        assert!(App::try_parse_from(["tgm", "license", "MIT", "--kind", "mit"]).is_err());
+        assert!(App::try_parse_from(["tgm", "license", "MIT", "--check"]).is_err());
+    }
 }
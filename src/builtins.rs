@@ -0,0 +1,141 @@
+//! built-in variables injected when app created, such as `@current_year@` and `@project_name@`
+use crate::models::Variable;
+use crate::VERSION;
+use chrono::{DateTime, Datelike, Local, SecondsFormat};
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+/// default format of `current_date`, and `date_format` global variable to customize it
+pub const DEFAULT_DATE_FORMAT: &str = "%m/%d/%Y";
+
+/// names of built-in variables
+pub const BUILTIN_NAMES: &[&str] = &[
+    "current_year",
+    "current_date",
+    "iso_date",
+    "iso_datetime",
+    "os_name",
+    "os_family",
+    "os_arch",
+    "project_dir",
+    "project_name",
+    "uuid",
+    "git_user_name",
+    "git_user_email",
+    "tgm_version",
+];
+
+/// built-in variables for the project directory, and `date_format` is chrono format for `current_date`
+pub fn builtin_variables(project_dir: &Path, date_format: Option<&str>) -> Vec<Variable> {
+    let now: DateTime<Local> = Local::now();
+    let project_dir = project_dir
+        .canonicalize()
+        .unwrap_or_else(|_| project_dir.to_path_buf());
+    let project_name = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let date_format = date_format.unwrap_or(DEFAULT_DATE_FORMAT);
+    let values = [
+        now.year().to_string(),
+        now.format(date_format).to_string(),
+        now.format("%Y-%m-%d").to_string(),
+        now.to_rfc3339_opts(SecondsFormat::Secs, false),
+        String::from(env::consts::OS),
+        String::from(env::consts::FAMILY),
+        String::from(env::consts::ARCH),
+        project_dir.display().to_string(),
+        project_name,
+        uuid::Uuid::new_v4().to_string(),
+        git_config("user.name"),
+        git_config("user.email"),
+        String::from(VERSION),
+    ];
+    to_variables(values)
+}
+
+/// built-in variables with fixed values for template tests, and snapshots are stable
+pub fn fixture_variables(project_name: &str) -> Vec<Variable> {
+    let values = [
+        "2020",
+        "01/01/2020",
+        "2020-01-01",
+        "2020-01-01T00:00:00+00:00",
+        "linux",
+        "unix",
+        "x86_64",
+        &format!("/tmp/{}", project_name),
+        project_name,
+        "00000000-0000-0000-0000-000000000000",
+        "tgm",
+        "tgm@example.com",
+        VERSION,
+    ];
+    to_variables(values.map(String::from))
+}
+
+fn to_variables(values: [String; 13]) -> Vec<Variable> {
+    BUILTIN_NAMES
+        .iter()
+        .zip(values)
+        .map(|(name, value)| Variable {
+            name: name.to_string(),
+            value: Some(value),
+            description: description(name).to_string(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn description(name: &str) -> &str {
+    match name {
+        "current_year" => "current year",
+        "current_date" => "current date with date_format",
+        "iso_date" => "current date in ISO 8601",
+        "iso_datetime" => "current date time in RFC 3339",
+        "os_name" => "OS name",
+        "os_family" => "OS family",
+        "os_arch" => "OS architecture",
+        "project_dir" => "absolute path of app directory",
+        "project_name" => "app directory name",
+        "uuid" => "random UUID",
+        "git_user_name" => "user.name in git config",
+        "git_user_email" => "user.email in git config",
+        "tgm_version" => "tgm version",
+        _ => "",
+    }
+}
+
+fn git_config(key: &str) -> String {
+    Command::new("git")
+        .args(["config", "--get", key])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_variables() {
+        let variables = builtin_variables(Path::new("tgm_data"), Some("%Y/%m/%d"));
+        let value = |name: &str| {
+            variables
+                .iter()
+                .find(|variable| variable.name == name)
+                .and_then(|variable| variable.value.clone())
+                .unwrap()
+        };
+        assert_eq!(variables.len(), BUILTIN_NAMES.len());
+        assert_eq!(value("project_name"), "tgm_data");
+        assert_eq!(value("current_date").len(), "2020/01/01".len());
+        assert_eq!(&value("iso_datetime")[..10], value("iso_date"));
+        assert_eq!(value("uuid").len(), 36);
+        assert_eq!(value("tgm_version"), VERSION);
+    }
+}
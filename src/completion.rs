@@ -0,0 +1,359 @@
+//! shell completion: static scripts by clap_complete, and dynamic completion of template names
+use crate::app::App;
+use clap::CommandFactory;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::EnvCompleter;
+use clap_complete::{env, shells, Generator};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tgm::models::Settings;
+use tgm::paths;
+
+/// supported shells
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell", "elvish"];
+
+/// environment variable to ask tgm for dynamic completion
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// cached remote templates from `tgm list --remote`
+pub fn remote_templates_cache() -> PathBuf {
+    paths::cache_dir().join("remote-templates.json")
+}
+
+/// names and descriptions of local templates, then cached remote templates
+pub fn template_names() -> Vec<(String, String)> {
+    let mut names: Vec<(String, String)> = Settings::load()
+        .unwrap_or_default()
+        .templates
+        .into_iter()
+        .map(|template| (template.name, template.description))
+        .collect();
+    let remote_templates: Vec<serde_json::Value> = fs::read_to_string(remote_templates_cache())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    for remote_template in remote_templates.iter() {
+        let field = |name: &str| {
+            remote_template
+                .get(name)
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let name = field("name");
+        if !name.is_empty() && !names.iter().any(|(local_name, _)| *local_name == name) {
+            names.push((name, field("description")));
+        }
+    }
+    names
+}
+
+/// completion candidates of template name, for `create`, `remove` and `info`
+pub fn template_candidates() -> Vec<CompletionCandidate> {
+    template_names()
+        .into_iter()
+        .map(|(name, description)| {
+            let help = Some(description).filter(|description| !description.is_empty());
+            CompletionCandidate::new(name).help(help.map(Into::into))
+        })
+        .collect()
+}
+
+/// write completion script of the shell, and dynamic script asks tgm for candidates, such as template names
+pub fn write_script(shell: &str, dynamic: bool, buf: &mut dyn Write) -> Result<(), String> {
+    if dynamic {
+        let completer: &dyn EnvCompleter = match shell {
+            "bash" => &env::Bash,
+            "zsh" => &env::Zsh,
+            "fish" => &env::Fish,
+            "powershell" => &env::Powershell,
+            "elvish" => &env::Elvish,
+            _ => return Err(format!("Unsupported shell: {}", shell)),
+        };
+        return completer
+            .write_registration(COMPLETE_VAR, "tgm", "tgm", "tgm", buf)
+            .map_err(|e| e.to_string());
+    }
+    match shell {
+        "bash" => generate(shells::Bash, buf),
+        "zsh" => generate(shells::Zsh, buf),
+        "fish" => generate(shells::Fish, buf),
+        "powershell" => generate(shells::PowerShell, buf),
+        "elvish" => generate(shells::Elvish, buf),
+        _ => return Err(format!("Unsupported shell: {}", shell)),
+    }
+    Ok(())
+}
+
+fn generate<G: Generator>(generator: G, buf: &mut dyn Write) {
+    clap_complete::generate(generator, &mut App::command(), "tgm", buf);
+}
+
+/// oh-my-zsh custom directory: `$ZSH_CUSTOM`, `ZSH_CUSTOM` in .zshrc, `$ZSH/custom`, then `~/.oh-my-zsh/custom`
+pub fn oh_my_zsh_custom_dir(home: &Path, zshrc_text: &str) -> PathBuf {
+    custom_dir_with_env(home, zshrc_text, |name| std::env::var(name).ok())
+}
+
+/// oh-my-zsh custom directory with environment variables from `env_var`
+fn custom_dir_with_env(
+    home: &Path,
+    zshrc_text: &str,
+    env_var: impl Fn(&str) -> Option<String>,
+) -> PathBuf {
+    let expand = |value: &str| {
+        let value = value.trim().trim_matches(['"', '\'']);
+        let home = home.display().to_string();
+        let value = value.replace("${HOME}", &home).replace("$HOME", &home);
+        match value.strip_prefix("~/") {
+            Some(relative_path) => Path::new(&home).join(relative_path),
+            None => PathBuf::from(value),
+        }
+    };
+    let assignment = |name: &str| {
+        zshrc_text.lines().rev().find_map(|line| {
+            let line = line.trim_start().trim_start_matches("export ");
+            line.strip_prefix(name)?.strip_prefix('=').map(expand)
+        })
+    };
+    env_var("ZSH_CUSTOM")
+        .filter(|value| !value.is_empty())
+        .map(|value| expand(&value))
+        .or_else(|| assignment("ZSH_CUSTOM"))
+        .or_else(|| {
+            env_var("ZSH")
+                .filter(|value| !value.is_empty())
+                .map(|value| expand(&value).join("custom"))
+        })
+        .or_else(|| assignment("ZSH").map(|zsh| zsh.join("custom")))
+        .unwrap_or_else(|| home.join(".oh-my-zsh").join("custom"))
+}
+
+/// line range of the effective `plugins=(...)` array, the last one, and comments are skipped
+fn find_plugins(lines: &[&str]) -> Option<(usize, usize)> {
+    let start = lines
+        .iter()
+        .rposition(|line| line.trim_start().starts_with("plugins=("))?;
+    let end = (start..lines.len()).find(|index| {
+        let line = lines[*index].split('#').next().unwrap_or_default();
+        line.contains(')')
+    })?;
+    Some((start, end))
+}
+
+/// plugin array line split into prefix with indentation or `plugins=(`, words, and suffix with `)` and comment
+fn split_plugin_line(line: &str, first: bool) -> (String, Vec<String>, String) {
+    let (prefix, body) = if first {
+        let index = line.find("plugins=(").unwrap_or_default() + "plugins=(".len();
+        line.split_at(index)
+    } else {
+        line.split_at(line.len() - line.trim_start().len())
+    };
+    let (words, suffix) = match body.find(['#', ')']) {
+        Some(index) => body.split_at(index),
+        None => (body, ""),
+    };
+    let words = words.split_whitespace().map(String::from).collect();
+    (prefix.to_string(), words, suffix.to_string())
+}
+
+fn join_plugin_line(prefix: &str, words: &[String], suffix: &str) -> String {
+    let words = words.join(" ");
+    let separator = if words.is_empty() || suffix.is_empty() || suffix.starts_with(')') {
+        ""
+    } else {
+        " "
+    };
+    format!("{}{}{}{}", prefix, words, separator, suffix)
+}
+
+/// add plugin to effective `plugins=(...)` array of .zshrc, and None if the plugin is present
+pub fn add_plugin(zshrc_text: &str, plugin: &str) -> Result<Option<String>, String> {
+    let mut lines: Vec<String> = zshrc_text.lines().map(String::from).collect();
+    let line_refs: Vec<&str> = zshrc_text.lines().collect();
+    let Some((start, end)) = find_plugins(&line_refs) else {
+        // declare plugins before oh-my-zsh is loaded
+        let index = line_refs
+            .iter()
+            .position(|line| line.contains("oh-my-zsh.sh") && !line.trim_start().starts_with('#'))
+            .ok_or_else(|| String::from("oh-my-zsh is not loaded in ~/.zshrc"))?;
+        lines.insert(index, format!("plugins=({})", plugin));
+        return Ok(Some(join_lines(zshrc_text, &lines)));
+    };
+    let present = (start..=end).any(|index| {
+        let (_, words, _) = split_plugin_line(line_refs[index], index == start);
+        words.iter().any(|word| word == plugin)
+    });
+    if present {
+        return Ok(None);
+    }
+    let (prefix, mut words, suffix) = split_plugin_line(line_refs[end], end == start);
+    if words.is_empty() && end > start {
+        // multi-line array with `)` on its own line: new line with the indentation of previous entry
+        let indent = line_refs[end - 1]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect::<String>();
+        lines.insert(end, format!("{}{}", indent, plugin));
+    } else {
+        words.push(plugin.to_string());
+        lines[end] = join_plugin_line(&prefix, &words, &suffix);
+    }
+    Ok(Some(join_lines(zshrc_text, &lines)))
+}
+
+/// remove plugin from `plugins=(...)` array of .zshrc, and None if the plugin is absent
+pub fn remove_plugin(zshrc_text: &str, plugin: &str) -> Option<String> {
+    let line_refs: Vec<&str> = zshrc_text.lines().collect();
+    let (start, end) = find_plugins(&line_refs)?;
+    let mut lines: Vec<String> = vec![];
+    let mut removed = false;
+    for (index, line) in line_refs.iter().enumerate() {
+        if index < start || index > end {
+            lines.push(line.to_string());
+            continue;
+        }
+        let (prefix, mut words, suffix) = split_plugin_line(line, index == start);
+        let count = words.len();
+        words.retain(|word| word != plugin);
+        if words.len() == count {
+            lines.push(line.to_string());
+            continue;
+        }
+        removed = true;
+        // line with the plugin only is removed
+        if index == start || !words.is_empty() || !suffix.is_empty() {
+            lines.push(join_plugin_line(&prefix, &words, &suffix));
+        }
+    }
+    if removed {
+        Some(join_lines(zshrc_text, &lines))
+    } else {
+        None
+    }
+}
+
+fn join_lines(text: &str, lines: &[String]) -> String {
+    let mut new_text = lines.join("\n");
+    if text.ends_with('\n') {
+        new_text.push('\n');
+    }
+    new_text
+}
+
+/// install tgm plugin for oh-my-zsh: completion in `$ZSH_CUSTOM/plugins/tgm`, and tgm in plugins of .zshrc.
+/// .zshrc is backed up as .zshrc.tgm.bak before the first change.
+pub fn install_oh_my_zsh(home: &Path) -> Result<PathBuf, String> {
+    let zshrc_file = home.join(".zshrc");
+    let zshrc_text = fs::read_to_string(&zshrc_file)
+        .map_err(|e| format!("Failed to read {}: {}", zshrc_file.display(), e))?;
+    let plugin_dir = oh_my_zsh_custom_dir(home, &zshrc_text)
+        .join("plugins")
+        .join("tgm");
+    fs::create_dir_all(&plugin_dir)
+        .map_err(|e| format!("Failed to create directory {}: {}", plugin_dir.display(), e))?;
+    let mut completion_file = fs::File::create(plugin_dir.join("_tgm"))
+        .map_err(|e| format!("Failed to create {}/_tgm: {}", plugin_dir.display(), e))?;
+    write_script("zsh", false, &mut completion_file)?;
+    if let Some(new_zshrc_text) = add_plugin(&zshrc_text, "tgm")? {
+        update_zshrc(&zshrc_file, &new_zshrc_text)?;
+    }
+    Ok(plugin_dir)
+}
+
+/// uninstall tgm plugin for oh-my-zsh: remove tgm from plugins of .zshrc and plugin directory
+pub fn uninstall_oh_my_zsh(home: &Path) -> Result<PathBuf, String> {
+    let zshrc_file = home.join(".zshrc");
+    let zshrc_text = fs::read_to_string(&zshrc_file).unwrap_or_default();
+    if let Some(new_zshrc_text) = remove_plugin(&zshrc_text, "tgm") {
+        update_zshrc(&zshrc_file, &new_zshrc_text)?;
+    }
+    let plugin_dir = oh_my_zsh_custom_dir(home, &zshrc_text)
+        .join("plugins")
+        .join("tgm");
+    if plugin_dir.exists() {
+        fs::remove_dir_all(&plugin_dir)
+            .map_err(|e| format!("Failed to remove {}: {}", plugin_dir.display(), e))?;
+    }
+    Ok(plugin_dir)
+}
+
+/// write .zshrc, and the backup keeps .zshrc before tgm changed it, so it's never overwritten
+fn update_zshrc(zshrc_file: &Path, zshrc_text: &str) -> Result<(), String> {
+    let backup_file = zshrc_file.with_extension("tgm.bak");
+    if !backup_file.exists() {
+        fs::copy(zshrc_file, &backup_file)
+            .map_err(|e| format!("Failed to back up {}: {}", zshrc_file.display(), e))?;
+    }
+    fs::write(zshrc_file, zshrc_text)
+        .map_err(|e| format!("Failed to write {}: {}", zshrc_file.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_names() {
+        paths::use_test_home();
+        let names = template_names();
+        assert!(names.iter().any(|(name, _)| name == "spring-boot-java"));
+        let mut script = vec![];
+        write_script("fish", true, &mut script).unwrap();
+        assert!(String::from_utf8(script).unwrap().contains("COMPLETE=fish"));
+        assert!(write_script("tcsh", false, &mut vec![]).is_err());
+    }
+
+    #[test]
+    fn test_zshrc_plugins() {
+        let zshrc_text = "# plugins=(rails)\nplugins=(git docker)\nsource $ZSH/oh-my-zsh.sh\n";
+        let new_text = add_plugin(zshrc_text, "tgm").unwrap().unwrap();
+        assert_eq!(
+            new_text,
+            "# plugins=(rails)\nplugins=(git docker tgm)\nsource $ZSH/oh-my-zsh.sh\n"
+        );
+        assert!(add_plugin(&new_text, "tgm").unwrap().is_none());
+        assert_eq!(remove_plugin(&new_text, "tgm").unwrap(), zshrc_text);
+        let zshrc_text = "plugins=(\n  git\n  docker # containers\n)\n";
+        let new_text = add_plugin(zshrc_text, "tgm").unwrap().unwrap();
+        assert_eq!(
+            new_text,
+            "plugins=(\n  git\n  docker # containers\n  tgm\n)\n"
+        );
+        assert_eq!(remove_plugin(&new_text, "tgm").unwrap(), zshrc_text);
+        let zshrc_text = "export ZSH=\"$HOME/.oh-my-zsh\"\nsource $ZSH/oh-my-zsh.sh\n";
+        let new_text = add_plugin(zshrc_text, "tgm").unwrap().unwrap();
+        assert!(new_text.contains("plugins=(tgm)\nsource $ZSH/oh-my-zsh.sh"));
+        assert!(add_plugin("alias ll='ls -l'\n", "tgm").is_err());
+        let home = Path::new("/home/jane");
+        let zshrc_text = "ZSH_CUSTOM=~/zsh-custom\n";
+        assert_eq!(
+            custom_dir_with_env(home, zshrc_text, |_| None),
+            Path::new("/home/jane/zsh-custom")
+        );
+        assert_eq!(
+            custom_dir_with_env(home, "", |name| {
+                (name == "ZSH").then(|| String::from("$HOME/.zsh"))
+            }),
+            Path::new("/home/jane/.zsh/custom")
+        );
+        assert_eq!(
+            custom_dir_with_env(home, "", |_| None),
+            Path::new("/home/jane/.oh-my-zsh/custom")
+        );
+    }
+
+    #[test]
+    fn test_update_zshrc() {
+        let home = std::env::temp_dir().join(format!("tgm-zsh-{}", std::process::id()));
+        fs::create_dir_all(&home).unwrap();
+        let zshrc_file = home.join(".zshrc");
+        fs::write(&zshrc_file, "plugins=(git)\n").unwrap();
+        update_zshrc(&zshrc_file, "plugins=(git tgm)\n").unwrap();
+        update_zshrc(&zshrc_file, "plugins=(git)\n").unwrap();
+        // backup keeps .zshrc before the first change
+        let backup_text = fs::read_to_string(home.join(".zshrc.tgm.bak")).unwrap();
+        assert_eq!(backup_text, "plugins=(git)\n");
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+//! file formats of template and settings: JSON, YAML and TOML, decided by file extension
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+
+/// file format
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum FileFormat {
+    Json,
+    #[value(alias = "yml")]
+    Yaml,
+    Toml,
+}
+
+/// template file names in precedence order
+pub const TEMPLATE_FILE_NAMES: &[&str] = &[
+    "template.json",
+    "template.yaml",
+    "template.yml",
+    "template.toml",
+];
+
+/// settings file names in precedence order
+pub const SETTINGS_FILE_NAMES: &[&str] = &[
+    "settings.json",
+    "settings.yaml",
+    "settings.yml",
+    "settings.toml",
+];
+
+impl FileFormat {
+    /// format by extension of path or url, and JSON for unknown extension
+    pub fn from_path(path: &str) -> FileFormat {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => FileFormat::Yaml,
+            Some("toml") => FileFormat::Toml,
+            _ => FileFormat::Json,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileFormat::Json => "json",
+            FileFormat::Yaml => "yaml",
+            FileFormat::Toml => "toml",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, text: &str) -> Result<T, String> {
+        match self {
+            FileFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            FileFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+            FileFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn to_text<T: Serialize>(self, value: &T) -> Result<String, String> {
+        match self {
+            FileFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            FileFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+            FileFormat::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// first existing file in the directory by precedence order
+pub fn find_file(dir: &Path, file_names: &[&str]) -> Option<std::path::PathBuf> {
+    file_names
+        .iter()
+        .map(|file_name| dir.join(file_name))
+        .find(|path| path.is_file())
+}
+
+/// whether the file name is template file, such as template.json or template.yaml
+pub fn is_template_file(file_name: &str) -> bool {
+    TEMPLATE_FILE_NAMES.contains(&file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AppTemplate;
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(FileFormat::from_path("template.yml"), FileFormat::Yaml);
+        assert_eq!(
+            FileFormat::from_path("https://example.com/template.toml?raw=true"),
+            FileFormat::Toml
+        );
+        assert_eq!(FileFormat::from_path("settings.json"), FileFormat::Json);
+        let yaml_text = r#"
+name: demo
+repository: https://github.com/tgm-templates/demo
+description: |
+  Demo template
+  with multi-line description
+variables:
+  - name: groupId
+    description: Maven groupId
+files: [pom.xml]
+"#;
+        let app_template: AppTemplate = FileFormat::Yaml.parse(yaml_text).unwrap();
+        assert_eq!(
+            app_template.description,
+            "Demo template\nwith multi-line description\n"
+        );
+        let toml_text = FileFormat::Toml.to_text(&app_template).unwrap();
+        let app_template: AppTemplate = FileFormat::Toml.parse(&toml_text).unwrap();
+        assert_eq!(app_template.variables.unwrap()[0].name, "groupId");
+        assert_eq!(app_template.files.unwrap(), vec!["pom.xml"]);
+    }
+}
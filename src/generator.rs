@@ -0,0 +1,465 @@
+//! app generation: fetch template into destination, then render template files with answers
+use crate::builtins;
+use crate::format::TEMPLATE_FILE_NAMES;
+use crate::harness::TESTS_DIR;
+use crate::licenses;
+use crate::lookup;
+use crate::manifest;
+use crate::models::{AppTemplate, Settings, Template};
+use crate::render::{copy_dir, render_files, render_paths};
+use crate::source::TemplateSource;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// files written by generator, and paths are relative to destination
+#[derive(Serialize, Debug, Default)]
+pub struct Report {
+    /// app directory
+    pub destination: PathBuf,
+    /// template files rendered with answers, and placeholders in paths are replaced
+    pub rendered_files: Vec<PathBuf>,
+    /// license files with SPDX identifier, such as `LICENSE-MIT` with `MIT`
+    pub license_files: Vec<(PathBuf, String)>,
+    /// manifests with license field updated, such as Cargo.toml and package.json
+    pub manifests: Vec<PathBuf>,
+    /// SPDX license expression of license files and manifests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// post create command of template, and caller runs it in destination
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_create: Option<String>,
+    /// problems without failing generation, such as illegal license expression
+    pub warnings: Vec<String>,
+}
+
+impl Report {
+    /// all written files: rendered files, license files and manifests
+    pub fn written_files(&self) -> Vec<&Path> {
+        let mut files: Vec<&Path> = self
+            .rendered_files
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(self.license_files.iter().map(|(path, _)| path.as_path()))
+            .chain(self.manifests.iter().map(PathBuf::as_path))
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+/// app generator from template source to destination directory.
+/// `generate` fetches and renders template, and `fetch` then `render` lets caller collect answers by template variables.
+pub struct Generator<'a> {
+    settings: &'a Settings,
+    template: Template,
+    /// local template directory, and template is cloned from repository if absent
+    template_dir: Option<PathBuf>,
+    destination: PathBuf,
+}
+
+impl<'a> Generator<'a> {
+    /// generator with template source: template name, local template directory, repository or template.json url
+    pub fn new(
+        settings: &'a Settings,
+        source: &str,
+        destination: &Path,
+    ) -> Result<Generator<'a>, String> {
+        let source_dir = Path::new(source);
+        if source_dir.is_dir() {
+            return Generator::with_template_dir(settings, source_dir, destination);
+        }
+        let template = if lookup::is_template_name(source) {
+            lookup::find_template(settings, source)?
+        } else {
+            lookup::load_source(settings, TemplateSource::parse(source)?)?.1
+        };
+        if template.repository.is_empty() {
+            return Err(format!("Template without repository: {}", source));
+        }
+        Ok(Generator {
+            settings,
+            template,
+            template_dir: None,
+            destination: destination.to_path_buf(),
+        })
+    }
+
+    /// generator with local template directory, and template is copied instead of cloned
+    pub fn with_template_dir(
+        settings: &'a Settings,
+        template_dir: &Path,
+        destination: &Path,
+    ) -> Result<Generator<'a>, String> {
+        let app_template = AppTemplate::from_dir(template_dir)?;
+        let template = Template {
+            name: app_template.name,
+            repository: template_dir.display().to_string(),
+            description: app_template.description,
+            tags: app_template.tags.unwrap_or_default(),
+            ..Default::default()
+        };
+        Ok(Generator {
+            settings,
+            template,
+            template_dir: Some(template_dir.to_path_buf()),
+            destination: destination.to_path_buf(),
+        })
+    }
+
+    pub fn template(&self) -> &Template {
+        &self.template
+    }
+
+    pub fn destination(&self) -> &Path {
+        &self.destination
+    }
+
+    /// fetch template and render it with answers
+    pub fn generate(&self, answers: &HashMap<String, String>) -> Result<Report, String> {
+        let app_template = self.fetch()?;
+        self.render(&app_template, answers)
+    }
+
+    /// copy or clone template into destination, and template file in destination is returned.
+    /// Destination is removed if failed.
+    pub fn fetch(&self) -> Result<AppTemplate, String> {
+        if self.destination.exists() {
+            return Err(format!(
+                "'{}' directory exists already",
+                self.destination.display()
+            ));
+        }
+        self.fetch_template()
+            .inspect_err(|_| self.remove_destination())
+    }
+
+    /// path relative to destination
+    fn relative_path(&self, path: PathBuf) -> PathBuf {
+        path.strip_prefix(&self.destination)
+            .map(Path::to_path_buf)
+            .unwrap_or(path)
+    }
+
+    /// remove destination after failure, so generation could be retried
+    fn remove_destination(&self) {
+        fs::remove_dir_all(&self.destination).unwrap_or_default();
+    }
+
+    fn fetch_template(&self) -> Result<AppTemplate, String> {
+        match &self.template_dir {
+            Some(template_dir) => copy_dir(template_dir, &self.destination, &[".git", TESTS_DIR])
+                .map_err(|e| format!("Failed to copy template: {}", e))?,
+            None => self.clone_repository()?,
+        }
+        AppTemplate::from_dir(&self.destination)
+            .map_err(|e| format!("Illegal template file, please run 'tgm lint': {}", e))
+    }
+
+    /// clone template repository, then move template's sub directory to destination
+    fn clone_repository(&self) -> Result<(), String> {
+        let template = &self.template;
+        let clone_dir = match &template.subdir {
+            Some(_) => {
+                let mut clone_dir = self.destination.clone().into_os_string();
+                clone_dir.push(".tgm-clone");
+                PathBuf::from(clone_dir)
+            }
+            None => self.destination.clone(),
+        };
+        let mut command = Command::new("git");
+        command.args(["clone", "--depth", "1"]);
+        if let Some(git_ref) = &template.git_ref {
+            command.args(["--branch", git_ref]);
+        }
+        let output = command
+            .arg(&template.repository)
+            .arg(&clone_dir)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if !output.status.success() {
+            fs::remove_dir_all(&clone_dir).unwrap_or_default();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Failed to clone {}: {}",
+                template.repository,
+                stderr.trim()
+            ));
+        }
+        if let Some(subdir) = &template.subdir {
+            let result = fs::rename(clone_dir.join(subdir), &self.destination);
+            fs::remove_dir_all(&clone_dir).unwrap_or_default();
+            result.map_err(|e| {
+                format!(
+                    "Failed to find {} in {}: {}",
+                    subdir, template.repository, e
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// variables with `@name@` keys: built-in variables, then answers, global variables or default values.
+    /// Values of template variables are validated with regex pattern, choices and type.
+    pub fn variables(
+        &self,
+        app_template: &AppTemplate,
+        answers: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, String> {
+        let mut variables = HashMap::new();
+        // built-in variables, and answers with the same name take precedence
+        let date_format = self.settings.find_variable_value("date_format");
+        for variable in builtins::builtin_variables(&self.destination, date_format.as_deref()) {
+            let value = answers.get(&variable.name).cloned().or(variable.value);
+            variables.insert(format!("@{}@", variable.name), value.unwrap_or_default());
+        }
+        let mut errors = vec![];
+        for v in app_template.variables.iter().flatten() {
+            // references in secret global variables, such as env:NPM_TOKEN, are resolved if no answer
+            let value = match answers.get(&v.name) {
+                Some(value) => Some(value.clone()),
+                None => self
+                    .settings
+                    .resolve_variable_value(&v.name)
+                    .map_err(|e| format!("Failed to resolve global variable '{}': {}", v.name, e))?
+                    .or(v.value.clone()),
+            };
+            let Some(value) = value else {
+                errors.push(format!("no answer for variable '{}'", v.name));
+                continue;
+            };
+            if let Err(reason) = v.validate(&value) {
+                errors.push(format!(
+                    "'{}' of variable '{}' {}",
+                    v.display_value(&value),
+                    v.name,
+                    reason
+                ));
+            }
+            variables.insert(format!("@{}@", v.name), value);
+        }
+        if errors.is_empty() {
+            Ok(variables)
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+
+    /// render template files and paths with answers, and write license files.
+    /// Git repository is re-initialized, and template file and test cases are removed.
+    /// Answers are validated before any file changed, and destination is removed if failed.
+    pub fn render(
+        &self,
+        app_template: &AppTemplate,
+        answers: &HashMap<String, String>,
+    ) -> Result<Report, String> {
+        self.render_template(app_template, answers)
+            .inspect_err(|_| self.remove_destination())
+    }
+
+    fn render_template(
+        &self,
+        app_template: &AppTemplate,
+        answers: &HashMap<String, String>,
+    ) -> Result<Report, String> {
+        let variables = self.variables(app_template, answers)?;
+        let files = app_template.files.as_deref().unwrap_or_default();
+        render_files(&self.destination, files, &variables)?;
+        render_paths(&self.destination, &variables)?;
+        let rendered_path = |file: &String| {
+            let path = variables
+                .iter()
+                .fold(file.clone(), |path, (k, v)| path.replace(k.as_str(), v));
+            PathBuf::from(path)
+        };
+        let mut report = Report {
+            destination: self.destination.clone(),
+            rendered_files: files.iter().map(rendered_path).collect(),
+            post_create: app_template
+                .post_create
+                .clone()
+                .filter(|post_create| !post_create.is_empty()),
+            ..Default::default()
+        };
+        self.write_license(&variables, &mut report);
+        // re-init
+        fs::remove_dir_all(self.destination.join(".git")).unwrap_or_default();
+        if let Err(e) = Command::new("git")
+            .arg("init")
+            .current_dir(&self.destination)
+            .output()
+        {
+            report
+                .warnings
+                .push(format!("Failed to init git repository: {}", e));
+        }
+        fs::remove_dir_all(self.destination.join(TESTS_DIR)).unwrap_or_default();
+        for file_name in TEMPLATE_FILE_NAMES {
+            fs::remove_file(self.destination.join(file_name)).unwrap_or_default();
+        }
+        Ok(report)
+    }
+
+    /// LICENSE by template's `license` variable or `open_source_license` global variable, and license field of manifests
+    fn write_license(&self, variables: &HashMap<String, String>, report: &mut Report) {
+        let variable_value = |name: &str| {
+            variables
+                .get(&format!("@{}@", name))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+        let Some(license_id) = variable_value("license")
+            .or_else(|| self.settings.find_variable_value("open_source_license"))
+            .filter(|license_id| !license_id.is_empty())
+        else {
+            return;
+        };
+        let registries = self.settings.registries.clone().unwrap_or_default();
+        let licenses = match licenses::parse_expression(&license_id, &registries) {
+            Ok(licenses) => licenses,
+            Err(e) => {
+                report
+                    .warnings
+                    .push(format!("{}, and LICENSE is not created", e));
+                return;
+            }
+        };
+        let author_name = variable_value("author_name")
+            .or_else(|| self.settings.find_variable_value("author_name"))
+            .or_else(|| variable_value("git_user_name"))
+            .unwrap_or_else(|| "Anonymous".to_owned());
+        let project_name = variable_value("project_name").unwrap_or_default();
+        if licenses.len() > 1 {
+            // template's own LICENSE is replaced by LICENSE-MIT and LICENSE-APACHE
+            fs::remove_file(self.destination.join("LICENSE")).unwrap_or_default();
+        }
+        // copyright year from built-in variable, and fixed year of template tests is used
+        let year = variable_value("current_year").unwrap_or_default();
+        match licenses::write_license_files(
+            &licenses,
+            &self.destination,
+            &year,
+            &author_name,
+            &project_name,
+            true,
+        ) {
+            Ok(license_files) => {
+                report.license_files = license_files
+                    .into_iter()
+                    .map(|(path, license_id)| (self.relative_path(path), license_id))
+                    .collect()
+            }
+            Err(e) => {
+                report.warnings.push(e);
+                return;
+            }
+        }
+        // AND, OR and WITH of the expression are kept in manifests
+        let license_id = licenses::spdx_expression(&license_id, &licenses);
+        let license_ids: Vec<&str> = licenses.iter().map(|license| license.id.as_ref()).collect();
+        match manifest::update_license(&self.destination, &license_id, &license_ids) {
+            Ok(file_names) => report
+                .manifests
+                .extend(file_names.into_iter().map(PathBuf::from)),
+            Err(e) => report.warnings.push(e),
+        }
+        report.license = Some(license_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        crate::paths::use_test_home();
+        let work_dir = std::env::temp_dir().join(format!("tgm-generator-{}", std::process::id()));
+        let template_dir = work_dir.join("template");
+        fs::create_dir_all(template_dir.join("src/@artifactId@")).unwrap();
+        fs::write(
+            template_dir.join("template.json"),
+            r#"{"name": "demo", "repository": "https://github.com/tgm-templates/demo", "description": "demo",
+            "variables": [{"name": "artifactId", "description": "artifactId", "pattern": "^[a-z]+$"},
+             {"name": "license", "description": "license", "value": "MIT", "choices": ["MIT", "Apache-2.0"]}],
+            "files": ["Cargo.toml", "src/@artifactId@/main.rs"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            template_dir.join("Cargo.toml"),
+            "[package]\nname = \"@artifactId@\"\n",
+        )
+        .unwrap();
+        fs::write(
+            template_dir.join("src/@artifactId@/main.rs"),
+            "// @artifactId@\n",
+        )
+        .unwrap();
+        let settings = Settings::default();
+        let destination = work_dir.join("app");
+        let absent = Generator {
+            settings: &settings,
+            template: Template {
+                repository: work_dir.join("absent").display().to_string(),
+                ..Default::default()
+            },
+            template_dir: None,
+            destination: destination.clone(),
+        };
+        assert!(absent.fetch().unwrap_err().starts_with("Failed to clone"));
+        assert!(!destination.exists());
+        let generator =
+            Generator::new(&settings, template_dir.to_str().unwrap(), &destination).unwrap();
+        assert_eq!(generator.template().name, "demo");
+        let answers = HashMap::from([("artifactId".to_string(), "Demo".to_string())]);
+        let error = generator.generate(&answers).unwrap_err();
+        assert_eq!(
+            error,
+            "'Demo' of variable 'artifactId' should match with '^[a-z]+$' regex pattern"
+        );
+        assert!(!destination.exists());
+        let answers = HashMap::from([("artifactId".to_string(), "demo".to_string())]);
+        let report = generator.generate(&answers).unwrap();
+        let text = fs::read_to_string(destination.join("src/demo/main.rs")).unwrap();
+        let cargo_toml = fs::read_to_string(destination.join("Cargo.toml")).unwrap();
+        let template_json_exists = destination.join("template.json").exists();
+        fs::remove_dir_all(&work_dir).unwrap();
+        assert_eq!(text, "// demo\n");
+        assert_eq!(
+            cargo_toml,
+            "[package]\nname = \"demo\"\nlicense = \"MIT\"\n"
+        );
+        assert!(!template_json_exists);
+        assert_eq!(report.license.as_deref(), Some("MIT"));
+        assert_eq!(
+            report.written_files(),
+            vec![
+                Path::new("Cargo.toml"),
+                Path::new("LICENSE"),
+                Path::new("src/demo/main.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_without_template_file() {
+        crate::paths::use_test_home();
+        let work_dir = std::env::temp_dir().join(format!("tgm-plain-{}", std::process::id()));
+        let template_dir = work_dir.join("template");
+        fs::create_dir_all(&template_dir).unwrap();
+        fs::write(template_dir.join("README.md"), "# demo\n").unwrap();
+        let settings = Settings::default();
+        let destination = work_dir.join("app");
+        let generator =
+            Generator::new(&settings, template_dir.to_str().unwrap(), &destination).unwrap();
+        let report = generator.generate(&HashMap::new());
+        let readme_exists = destination.join("README.md").exists();
+        fs::remove_dir_all(&work_dir).unwrap();
+        let report = report.unwrap();
+        assert!(readme_exists);
+        assert_eq!(report.post_create, None);
+    }
+}
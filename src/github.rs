@@ -0,0 +1,212 @@
+//! GitHub REST API client with pagination, token and rate limit support
+use crate::models::Settings;
+use chrono::{DateTime, Local};
+use reqwest::blocking::{Client, Response};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::env;
+
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// GitHub API endpoint, api.github.com or GitHub Enterprise, such as https://github.example.com/api/v3
+pub struct GithubApi {
+    pub base_url: String,
+    pub token: Option<String>,
+}
+
+impl GithubApi {
+    pub fn new(base_url: &str, token: Option<String>) -> GithubApi {
+        GithubApi {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    /// api url from GITHUB_API_URL or settings, token from GITHUB_TOKEN, GH_TOKEN or settings
+    pub fn from_settings(settings: &Settings) -> GithubApi {
+        let base_url = env::var("GITHUB_API_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .or_else(|| settings.github_api.clone())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        let token = ["GITHUB_TOKEN", "GH_TOKEN"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|token| !token.is_empty())
+            .or_else(|| settings.github_token.clone());
+        GithubApi::new(&base_url, token)
+    }
+
+    /// get json object from api path, such as `/repos/{owner}/{repo}`
+    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, String> {
+        let url = format!("{}{}", self.base_url, path);
+        self.send(&url)?.json::<T>().map_err(|e| e.to_string())
+    }
+
+    /// get all items of a list api, and follow `Link: <url>; rel="next"` header for next pages
+    pub fn get_pages<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, String> {
+        let mut items: Vec<T> = vec![];
+        let mut next_url = Some(format!("{}{}", self.base_url, path));
+        while let Some(url) = next_url {
+            let response = self.send(&url)?;
+            next_url = response
+                .headers()
+                .get("link")
+                .and_then(|link| link.to_str().ok())
+                .and_then(next_page_url);
+            items.extend(response.json::<Vec<T>>().map_err(|e| e.to_string())?);
+        }
+        Ok(items)
+    }
+
+    fn send(&self, url: &str) -> Result<Response, String> {
+        let client = Client::builder().build().map_err(|e| e.to_string())?;
+        let mut request = client
+            .get(url)
+            .header("Accept", "application/vnd.github.v3+json")
+            .header("User-Agent", "Awesome-tgm-App");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .map_err(|e| format!("Failed to request {}: {}", url, e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let remaining = header_value(&response, "x-ratelimit-remaining");
+        if (status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS)
+            && remaining.as_deref() == Some("0")
+        {
+            let reset_time = header_value(&response, "x-ratelimit-reset")
+                .and_then(|reset| reset.parse::<i64>().ok())
+                .and_then(|reset| DateTime::from_timestamp(reset, 0))
+                .map(|reset| {
+                    reset
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_else(|| "unknown".to_string());
+            let mut message = format!("GitHub API rate limit exceeded, reset at {}", reset_time);
+            if self.token.is_none() {
+                message.push_str(", please set GITHUB_TOKEN or GH_TOKEN to raise the limit");
+            }
+            return Err(message);
+        }
+        let body = response.text().unwrap_or_default();
+        let detail = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|json| json["message"].as_str().map(String::from))
+            .unwrap_or(body);
+        Err(format!("GitHub API {} for {}: {}", status, url, detail))
+    }
+}
+
+fn header_value(response: &Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+/// url with rel="next" from Link header
+fn next_page_url(link_header: &str) -> Option<String> {
+    link_header.split(',').find_map(|link| {
+        let mut parts = link.split(';');
+        let url = parts.next()?.trim();
+        if parts.any(|param| param.trim() == r#"rel="next""#) {
+            Some(
+                url.trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string(),
+            )
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GithubRepo;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// start mock http server, and reply responses built with the server's base url in order
+    fn mock_server(responses: impl FnOnce(&str) -> Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let responses = responses(&base_url);
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        base_url
+    }
+
+    fn http_response(status: &str, headers: &[String], body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+            status,
+            body.len(),
+            headers.iter().map(|h| format!("{}\r\n", h)).collect::<String>(),
+            body
+        )
+    }
+
+    #[test]
+    fn test_next_page_url() {
+        let link = r#"<https://api.github.com/orgs/demo/repos?page=2>; rel="next", <https://api.github.com/orgs/demo/repos?page=5>; rel="last""#;
+        assert_eq!(
+            next_page_url(link).as_deref(),
+            Some("https://api.github.com/orgs/demo/repos?page=2")
+        );
+        assert!(next_page_url(r#"<https://api.github.com/x?page=1>; rel="prev""#).is_none());
+    }
+
+    #[test]
+    fn test_fetch_repos_with_pagination() {
+        let base_url = mock_server(|base_url| {
+            let page1 = r#"[{"name":"vue","full_name":"demo/vue","description":null,"html_url":"https://github.com/demo/vue"}]"#;
+            let page2 = r#"[{"name":"axum","full_name":"demo/axum","description":"Axum","html_url":"https://github.com/demo/axum"}]"#;
+            let link = format!(r#"Link: <{}/orgs/demo/repos?page=2>; rel="next""#, base_url);
+            vec![
+                http_response("200 OK", &[link], page1),
+                http_response("200 OK", &[], page2),
+            ]
+        });
+        let api = GithubApi::new(&base_url, Some("token".to_string()));
+        let repos = GithubRepo::fetch_tgm_template_repos(&api, "demo").unwrap();
+        assert_eq!(repos.len(), 2);
+        assert_eq!(repos[0].name, "axum");
+        assert_eq!(repos[1].description, "");
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let base_url = mock_server(|_| {
+            let headers = [
+                "X-RateLimit-Remaining: 0".to_string(),
+                "X-RateLimit-Reset: 1700000000".to_string(),
+            ];
+            let body = r#"{"message":"API rate limit exceeded"}"#;
+            vec![http_response("403 Forbidden", &headers, body)]
+        });
+        let api = GithubApi::new(&base_url, None);
+        let error = GithubRepo::fetch_tgm_template_repos(&api, "demo").unwrap_err();
+        assert!(error.contains("rate limit exceeded, reset at"));
+        assert!(error.contains("GITHUB_TOKEN"));
+    }
+}
@@ -0,0 +1,299 @@
+//! template test harness: render test cases with answers and compare with snapshot trees
+use crate::builtins::{fixture_variables, BUILTIN_NAMES};
+use crate::generator::Generator;
+use crate::models::{AppTemplate, Settings};
+use crate::render::copy_dir;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// directory of test cases in template: `tgm-tests/<case>.json` and `tgm-tests/snapshots/<case>/`.
+/// It's not `tests`, which belongs to the app in many templates, such as Rust integration tests, and it's removed when app created.
+pub const TESTS_DIR: &str = "tgm-tests";
+
+/// test case with answers of template variables and optional verify command
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// command to run in rendered directory, such as `cargo check`
+    pub verify: Option<String>,
+}
+
+/// result of test case
+pub struct CaseReport {
+    pub name: String,
+    pub failures: Vec<String>,
+}
+
+impl CaseReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// load test cases sorted by name
+pub fn load_cases(template_dir: &Path) -> Result<Vec<(String, TestCase)>, String> {
+    let tests_dir = template_dir.join(TESTS_DIR);
+    let entries = fs::read_dir(&tests_dir)
+        .map_err(|e| format!("Failed to read {}: {}", tests_dir.display(), e))?;
+    let mut cases = vec![];
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().is_some_and(|ext| ext == "json") && path.is_file() {
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let case: TestCase = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+                .map_err(|e| format!("Illegal test case {}: {}", path.display(), e))?;
+            cases.push((name, case));
+        }
+    }
+    cases.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(cases)
+}
+
+/// snapshot directory of test case
+pub fn snapshot_dir(template_dir: &Path, case_name: &str) -> PathBuf {
+    template_dir
+        .join(TESTS_DIR)
+        .join("snapshots")
+        .join(case_name)
+}
+
+/// render test case into temp directory with the same generator as `tgm create`,
+/// then compare with snapshot or update snapshot, and run verify command
+pub fn run_case(
+    template_dir: &Path,
+    app_template: &AppTemplate,
+    case_name: &str,
+    case: &TestCase,
+    update: bool,
+) -> CaseReport {
+    let mut report = CaseReport {
+        name: case_name.to_string(),
+        failures: vec![],
+    };
+    let answers = match case_answers(app_template, case_name, case) {
+        Ok(answers) => answers,
+        Err(failures) => {
+            report.failures = failures;
+            return report;
+        }
+    };
+    let render_dir =
+        std::env::temp_dir().join(format!("tgm-test-{}-{}", std::process::id(), case_name));
+    fs::remove_dir_all(&render_dir).unwrap_or_default();
+    // global variables of user's settings never change snapshots
+    let settings = Settings::default();
+    let result = Generator::with_template_dir(&settings, template_dir, &render_dir)
+        .and_then(|generator| generator.generate(&answers));
+    if let Err(e) = result {
+        report.failures.push(e);
+    } else {
+        let snapshot = snapshot_dir(template_dir, case_name);
+        if update {
+            fs::remove_dir_all(&snapshot).unwrap_or_default();
+            if let Err(e) = copy_dir(&render_dir, &snapshot, &[".git"]) {
+                report
+                    .failures
+                    .push(format!("Failed to update snapshot: {}", e));
+            }
+        } else if !snapshot.is_dir() {
+            report.failures.push(format!(
+                "snapshot {} not found, please run 'tgm test --update'",
+                snapshot.display()
+            ));
+        } else {
+            report.failures.extend(compare_dirs(&snapshot, &render_dir));
+        }
+        if let Some(verify) = &case.verify {
+            if let Err(e) = run_verify(verify, &render_dir) {
+                report.failures.push(e);
+            }
+        }
+    }
+    fs::remove_dir_all(&render_dir).unwrap_or_default();
+    report
+}
+
+/// answers of test case with built-in variables of fixed values, and answers must be declared variables.
+/// Default values are used for absent answers, and answers are validated by generator.
+fn case_answers(
+    app_template: &AppTemplate,
+    case_name: &str,
+    case: &TestCase,
+) -> Result<HashMap<String, String>, Vec<String>> {
+    let template_variables = app_template.variables.as_deref().unwrap_or_default();
+    let failures: Vec<String> = case
+        .variables
+        .keys()
+        .filter(|name| {
+            !BUILTIN_NAMES.contains(&name.as_str())
+                && !template_variables.iter().any(|v| &v.name == *name)
+        })
+        .map(|name| format!("unknown variable '{}' in answers", name))
+        .collect();
+    if !failures.is_empty() {
+        return Err(failures);
+    }
+    let mut answers: HashMap<String, String> = fixture_variables(case_name)
+        .into_iter()
+        .map(|variable| (variable.name, variable.value.unwrap_or_default()))
+        .collect();
+    answers.extend(case.variables.clone());
+    Ok(answers)
+}
+
+fn run_verify(verify: &str, render_dir: &Path) -> Result<(), String> {
+    let parts: Vec<&str> = verify.split_whitespace().collect();
+    let Some((command, args)) = parts.split_first() else {
+        return Ok(());
+    };
+    let output = Command::new(command)
+        .args(args)
+        .current_dir(render_dir)
+        .output()
+        .map_err(|e| format!("Failed to run verify command '{}': {}", verify, e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr.lines().collect();
+    let tail = lines[lines.len().saturating_sub(10)..].join("\n");
+    Err(format!(
+        "verify command '{}' failed with {}\n{}",
+        verify, output.status, tail
+    ))
+}
+
+/// differences between snapshot and rendered directories
+fn compare_dirs(snapshot_dir: &Path, render_dir: &Path) -> Vec<String> {
+    let expected = list_files(snapshot_dir, Path::new(""));
+    let actual = list_files(render_dir, Path::new(""));
+    let mut failures = vec![];
+    for file in expected.difference(&actual) {
+        failures.push(format!("missing file: {}", file));
+    }
+    for file in actual.difference(&expected) {
+        failures.push(format!("unexpected file: {}", file));
+    }
+    for file in expected.intersection(&actual) {
+        let expected_bytes = fs::read(snapshot_dir.join(file)).unwrap_or_default();
+        let actual_bytes = fs::read(render_dir.join(file)).unwrap_or_default();
+        if expected_bytes == actual_bytes {
+            continue;
+        }
+        let expected_text = String::from_utf8_lossy(&expected_bytes);
+        let actual_text = String::from_utf8_lossy(&actual_bytes);
+        let mut expected_lines = expected_text.lines();
+        let mut actual_lines = actual_text.lines();
+        let mut line_number = 1;
+        loop {
+            let (expected_line, actual_line) = (expected_lines.next(), actual_lines.next());
+            if expected_line != actual_line {
+                failures.push(format!(
+                    "{}:{}: expected '{}', got '{}'",
+                    file,
+                    line_number,
+                    expected_line.unwrap_or_default(),
+                    actual_line.unwrap_or_default()
+                ));
+                break;
+            }
+            if expected_line.is_none() {
+                // same lines with different line endings
+                failures.push(format!("{}: content differs", file));
+                break;
+            }
+            line_number += 1;
+        }
+    }
+    failures
+}
+
+/// relative paths of files in directory, with `/` separator
+fn list_files(base_dir: &Path, relative_dir: &Path) -> BTreeSet<String> {
+    let mut files = BTreeSet::new();
+    let Ok(entries) = fs::read_dir(base_dir.join(relative_dir)) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        // git repository of rendered app is re-initialized
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let relative_path = relative_dir.join(entry.file_name());
+        if entry.path().is_dir() {
+            files.extend(list_files(base_dir, &relative_path));
+        } else {
+            let names: Vec<String> = relative_path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            files.insert(names.join("/"));
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_case() {
+        crate::paths::use_test_home();
+        let template_dir = std::env::temp_dir().join(format!("tgm-harness-{}", std::process::id()));
+        fs::create_dir_all(template_dir.join(TESTS_DIR)).unwrap();
+        fs::write(
+            template_dir.join("template.json"),
+            r#"{"name": "demo", "repository": "https://github.com/tgm-templates/demo", "description": "demo", "variables": [{"name": "groupId", "description": "Maven groupId"},
+             {"name": "version", "description": "version", "value": "1.0"},
+             {"name": "license", "description": "license", "value": "MIT"}], "files": ["pom.xml"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            template_dir.join("pom.xml"),
+            "<groupId>@groupId@</groupId>\n<version>@version@</version>\n",
+        )
+        .unwrap();
+        fs::write(
+            template_dir.join(TESTS_DIR).join("basic.json"),
+            r#"{"variables": {"groupId": "org.demo"}}"#,
+        )
+        .unwrap();
+        let app_template = AppTemplate::from_file(&template_dir.join("template.json")).unwrap();
+        let cases = load_cases(&template_dir).unwrap();
+        assert_eq!(cases.len(), 1);
+        let (name, case) = &cases[0];
+        // snapshot absent
+        assert!(!run_case(&template_dir, &app_template, name, case, false).passed());
+        assert!(run_case(&template_dir, &app_template, name, case, true).passed());
+        let snapshot = snapshot_dir(&template_dir, name);
+        assert_eq!(
+            fs::read_to_string(snapshot.join("pom.xml")).unwrap(),
+            "<groupId>org.demo</groupId>\n<version>1.0</version>\n"
+        );
+        assert!(!snapshot.join("template.json").exists());
+        // license is written as `tgm create` does, with copyright year and holder of fixtures
+        assert!(fs::read_to_string(snapshot.join("LICENSE"))
+            .unwrap()
+            .contains("Copyright (c) 2020 tgm"));
+        assert!(run_case(&template_dir, &app_template, name, case, false).passed());
+        // template changed
+        fs::write(
+            template_dir.join("pom.xml"),
+            "<groupId>@groupId@</groupId>\n",
+        )
+        .unwrap();
+        let report = run_case(&template_dir, &app_template, name, case, false);
+        fs::remove_dir_all(&template_dir).unwrap();
+        assert_eq!(
+            report.failures,
+            vec!["pom.xml:2: expected '<version>1.0</version>', got ''"]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! tgm library: settings, template lookup in local settings, registries and GitHub, and app generation.
+//!
+//! `tgm` CLI is a thin layer over the library, and library functions never print or read stdin.
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use std::path::Path;
+//! use tgm::{Generator, Settings};
+//!
+//! let settings = Settings::load().unwrap();
+//! let answers = HashMap::from([("groupId".to_string(), "com.acme".to_string())]);
+//! let generator = Generator::new(&settings, "spring-boot-java", Path::new("demo")).unwrap();
+//! let report = generator.generate(&answers).unwrap();
+//! for file in report.written_files() {
+//!     println!("{}", file.display());
+//! }
+//! ```
+#![doc(html_logo_url = "https://avatars2.githubusercontent.com/u/71794210?s=200&v=4")]
+
+pub mod builtins;
+pub mod format;
+pub mod generator;
+pub mod github;
+pub mod harness;
+pub mod licenses;
+pub mod lint;
+pub mod lookup;
+pub mod manifest;
+pub mod models;
+pub mod paths;
+pub mod registry;
+pub mod render;
+pub mod scaffold;
+pub mod search;
+pub mod secret;
+pub mod source;
+
+pub use generator::{Generator, Report};
+pub use lookup::find_template;
+pub use models::{AppTemplate, Settings, Template, Variable};
+pub use registry::{RegistryEntry, RegistryIndex};
+
+pub const VERSION: &str = "0.10.0";
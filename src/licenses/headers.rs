@@ -0,0 +1,325 @@
+//! license headers in source files, with comment syntax by file extension
+use super::{replace_placeholders, License};
+use crate::scaffold::SKIPPED_DIRS;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// comment syntax of source file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommentStyle {
+    /// line comment with prefix, such as `//` and `#`
+    Line(&'static str),
+    /// block comment with open, line prefix and close, such as `/*`, ` * ` and ` */`
+    Block(&'static str, &'static str, &'static str),
+}
+
+/// header status of source file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeaderStatus {
+    Present,
+    Inserted,
+    Updated,
+}
+
+const SPDX_TAG: &str = "SPDX-License-Identifier:";
+
+/// comment style by file extension or file name, and None for unsupported file
+pub fn comment_style(path: &Path) -> Option<CommentStyle> {
+    let file_name = path.file_name()?.to_str()?;
+    if ["Dockerfile", "Makefile", "Justfile"].contains(&file_name) {
+        return Some(CommentStyle::Line("#"));
+    }
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "rs" | "go" | "java" | "kt" | "kts" | "scala" | "groovy" | "gradle" | "swift" | "c"
+        | "h" | "cc" | "cpp" | "hpp" | "cs" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx"
+        | "dart" | "proto" | "zig" => Some(CommentStyle::Line("//")),
+        "py" | "rb" | "sh" | "bash" | "zsh" | "fish" | "pl" | "r" | "toml" | "yaml" | "yml"
+        | "properties" | "tf" | "ps1" | "nix" | "ex" | "exs" | "cmake" => {
+            Some(CommentStyle::Line("#"))
+        }
+        "html" | "htm" | "xhtml" | "xml" | "vue" | "svelte" | "svg" => {
+            Some(CommentStyle::Block("<!--", "  ", "-->"))
+        }
+        "css" | "scss" | "less" => Some(CommentStyle::Block("/*", " * ", " */")),
+        _ => None,
+    }
+}
+
+impl License {
+    /// license notice from "How to Apply" appendix, such as Apache's and GPL's, and None if absent
+    fn notice(&self) -> Option<String> {
+        let start = [
+            "Copyright [yyyy]",
+            "<one line to give",
+            "This Source Code Form is subject",
+        ]
+        .iter()
+        .find_map(|marker| self.text.find(marker))?;
+        let start = self.text[..start].rfind('\n').map_or(0, |index| index + 1);
+        let notice = &self.text[start..];
+        let end = ["Also add information", "If it is not possible"]
+            .iter()
+            .find_map(|marker| notice.find(marker))
+            .unwrap_or(notice.len());
+        let lines: Vec<&str> = notice[..end].trim_end().lines().collect();
+        let indent = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let lines: Vec<&str> = lines
+            .iter()
+            .map(|line| line.get(indent..).unwrap_or_default())
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    /// header lines: copyright and SPDX identifier, or full license notice if `full` is true.
+    /// Short licenses without notice, such as MIT and BSD, use the whole license text as full header.
+    pub fn header_lines(
+        &self,
+        year: &str,
+        author_name: &str,
+        project_name: &str,
+        full: bool,
+    ) -> Result<Vec<String>, String> {
+        let mut lines = vec![];
+        if full {
+            let notice = match self.notice() {
+                Some(notice) => notice,
+                None if self.text.lines().count() <= 40 => self.text.trim_end().to_string(),
+                None => {
+                    return Err(format!(
+                        "{} has no license notice for source files, and please use SPDX header",
+                        self.id
+                    ))
+                }
+            };
+            lines.extend(
+                replace_placeholders(&notice, year, author_name, project_name)
+                    .lines()
+                    .map(String::from),
+            );
+            lines.push(String::new());
+        } else {
+            lines.push(format!("Copyright (c) {} {}", year, author_name));
+        }
+        lines.push(format!("{} {}", SPDX_TAG, self.id));
+        Ok(lines)
+    }
+}
+
+fn comment_lines(style: CommentStyle, lines: &[String]) -> Vec<String> {
+    match style {
+        CommentStyle::Line(prefix) => lines
+            .iter()
+            .map(|line| format!("{} {}", prefix, line).trim_end().to_string())
+            .collect(),
+        CommentStyle::Block(open, prefix, close) => {
+            let mut comment = vec![open.to_string()];
+            comment.extend(
+                lines
+                    .iter()
+                    .map(|line| format!("{}{}", prefix, line).trim_end().to_string()),
+            );
+            comment.push(close.to_string());
+            comment
+        }
+    }
+}
+
+/// line range of existing license header comment with SPDX identifier, which ends tgm's headers.
+/// Other comments, such as third-party copyright notices, are not headers and kept.
+fn find_header(lines: &[&str], start: usize, style: CommentStyle) -> Option<(usize, usize)> {
+    let end = match style {
+        CommentStyle::Line(prefix) => {
+            let prefix_space = format!("{} ", prefix);
+            start
+                + lines[start..]
+                    .iter()
+                    .take_while(|line| **line == prefix || line.starts_with(&prefix_space))
+                    .count()
+        }
+        CommentStyle::Block(open, _, close) => {
+            if !lines.get(start)?.starts_with(open) {
+                return None;
+            }
+            start
+                + lines[start..]
+                    .iter()
+                    .position(|line| line.contains(close.trim()))?
+                + 1
+        }
+    };
+    let is_header = lines[start..end].iter().any(|line| line.contains(SPDX_TAG));
+    if is_header {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+/// insert or update license header, and header lines are created by copyright year.
+/// Year of existing header is kept, so the header is stable across years.
+pub fn apply_header(
+    text: &str,
+    style: CommentStyle,
+    year: &str,
+    header_lines: &dyn Fn(&str) -> Vec<String>,
+) -> (HeaderStatus, String) {
+    let lines: Vec<&str> = text.lines().collect();
+    // shebang of `#` comment style and XML declaration stay on the first line,
+    // and Rust inner attribute, such as `#![allow(dead_code)]`, is not shebang
+    let is_shebang = |line: &str| style == CommentStyle::Line("#") && line.starts_with("#!");
+    let start = match lines.first() {
+        Some(line) if is_shebang(line) || line.starts_with("<?xml") => 1,
+        _ => 0,
+    };
+    let existing = find_header(&lines, start, style);
+    let year = existing
+        .and_then(|(begin, end)| {
+            let year_regex = Regex::new(r"Copyright.*?(\d{4}(\s*-\s*\d{4})?)").unwrap();
+            lines[begin..end]
+                .iter()
+                .find_map(|line| year_regex.captures(line))
+                .map(|captures| captures[1].to_string())
+        })
+        .unwrap_or_else(|| year.to_string());
+    let header = comment_lines(style, &header_lines(&year));
+    let (status, rest) = match existing {
+        Some((begin, end)) if lines[begin..end] == header[..] => {
+            return (HeaderStatus::Present, text.to_string())
+        }
+        Some((_, end)) => (HeaderStatus::Updated, lines[end..].to_vec()),
+        None => {
+            let mut rest = vec![""];
+            rest.extend(lines[start..].iter().skip_while(|line| line.is_empty()));
+            (HeaderStatus::Inserted, rest)
+        }
+    };
+    let mut new_lines: Vec<&str> = lines[..start].to_vec();
+    new_lines.extend(header.iter().map(|line| line.as_str()));
+    new_lines.extend(rest);
+    let mut new_text = new_lines.join("\n");
+    if text.ends_with('\n') || text.is_empty() {
+        new_text.push('\n');
+    }
+    (status, new_text)
+}
+
+/// source files with supported comment style in the directory, and hidden and skipped directories are ignored
+pub fn source_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if file_name.starts_with('.') || SKIPPED_DIRS.contains(&file_name.as_str()) {
+                continue;
+            }
+            if path.is_dir() {
+                files.extend(source_files(&path));
+            } else if comment_style(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// insert or update license headers of source files in the directory, and files are untouched if `check` is true.
+/// Files without up-to-date header are returned with status.
+pub fn license_headers(
+    dir: &Path,
+    license: &License,
+    year: &str,
+    author_name: &str,
+    project_name: &str,
+    full: bool,
+    check: bool,
+) -> Result<Vec<(PathBuf, HeaderStatus)>, String> {
+    // fail early if license has no full header
+    license.header_lines(year, author_name, project_name, full)?;
+    let header_lines = |year: &str| {
+        license
+            .header_lines(year, author_name, project_name, full)
+            .unwrap_or_default()
+    };
+    let mut changes = vec![];
+    for path in source_files(dir) {
+        let style = comment_style(&path).unwrap();
+        // binary and non UTF-8 files are skipped
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let (status, new_text) = apply_header(&text, style, year, &header_lines);
+        if status == HeaderStatus::Present {
+            continue;
+        }
+        if !check {
+            fs::write(&path, new_text)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        changes.push((path, status));
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::licenses::find_license;
+
+    #[test]
+    fn test_apply_header() {
+        crate::paths::use_test_home();
+        let mit = find_license("MIT").unwrap();
+        let header_lines = |year: &str| mit.header_lines(year, "Jane", "demo", false).unwrap();
+        let style = comment_style(Path::new("main.rs")).unwrap();
+        let (status, text) = apply_header("fn main() {}\n", style, "2020", &header_lines);
+        let (_, lib_text) = apply_header("#![allow(dead_code)]\n", style, "2020", &header_lines);
+        assert_eq!(
+            lib_text,
+            "// Copyright (c) 2020 Jane\n// SPDX-License-Identifier: MIT\n\n#![allow(dead_code)]\n"
+        );
+        let sh_style = comment_style(Path::new("run.sh")).unwrap();
+        let (_, sh_text) = apply_header("#!/bin/sh\necho\n", sh_style, "2020", &header_lines);
+        assert!(sh_text.starts_with("#!/bin/sh\n# Copyright (c) 2020 Jane\n"));
+        assert_eq!(status, HeaderStatus::Inserted);
+        assert_eq!(
+            text,
+            "// Copyright (c) 2020 Jane\n// SPDX-License-Identifier: MIT\n\nfn main() {}\n"
+        );
+        // idempotent, and year of existing header is kept
+        let (status, same_text) = apply_header(&text, style, "2021", &header_lines);
+        assert_eq!(status, HeaderStatus::Present);
+        assert_eq!(same_text, text);
+        let apache = find_license("Apache-2.0").unwrap();
+        let header_lines = |year: &str| apache.header_lines(year, "Jane", "demo", true).unwrap();
+        let (status, text) = apply_header(&text, style, "2021", &header_lines);
+        assert_eq!(status, HeaderStatus::Updated);
+        assert!(
+            text.starts_with("// Copyright 2020 Jane\n//\n// Licensed under the Apache License")
+        );
+        assert!(text.ends_with("// SPDX-License-Identifier: Apache-2.0\n\nfn main() {}\n"));
+        // third-party copyright notice is kept below the new header
+        let go = "// Copyright 2019 Google LLC\n// Use of this source code is governed by a BSD-style license.\n\npackage demo\n";
+        let (status, text) = apply_header(go, style, "2021", &header_lines);
+        assert_eq!(status, HeaderStatus::Inserted);
+        assert!(text.starts_with("// Copyright 2021 Jane\n"));
+        assert!(text.ends_with(&format!("Apache-2.0\n\n{}", go)));
+        let style = comment_style(Path::new("pom.xml")).unwrap();
+        let xml = "<?xml version=\"1.0\"?>\n<project/>\n";
+        let (_, text) = apply_header(xml, style, "2020", &header_lines);
+        assert!(text.starts_with("<?xml version=\"1.0\"?>\n<!--\n  Copyright 2020 Jane\n"));
+        assert!(text.ends_with("-->\n\n<project/>\n"));
+        assert!(find_license("EPL-2.0")
+            .unwrap()
+            .header_lines("2020", "Jane", "demo", true)
+            .is_err());
+    }
+}
@@ -1,35 +1,611 @@
-//! Open source licenses for Apache License 2.0, MIT License, ISC License, GNU GPLv3, GNU LGPLv3 and Mozilla Public License 2.0
+//! Open source licenses by SPDX identifier, such as `Apache-2.0`, `MIT`, `BSD-3-Clause` and `GPL-3.0-only`,
+//! and custom licenses from `licenses` directory of tgm config directory and registries
+use crate::paths;
+use crate::registry::{self, RegistryIndex};
 use chrono::{DateTime, Datelike, Local};
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
-pub fn get_license(license_name: &str, author_name: &str) -> String {
-    let name = license_name.to_uppercase();
-    let now: DateTime<Local> = Local::now();
-    let year: &str = &now.year().to_string();
-    return if name.contains("APACHE") {
-        APACHE_LICENSE_20
-            .replace("[yyyy]", year)
-            .replace("[name of copyright owner]", author_name)
-    } else if name.contains("ISC") {
-        ISC_LICENSE
-            .replace("[year]", year)
-            .replace("[fullname]", author_name)
-    } else if name.contains("MOZILLA") {
-        String::from(MOZILLA_PUBLIC_LICENSE_20)
-    } else if name.contains("MIT") {
-        MIT.replace("[year]", year)
-            .replace("[fullname]", author_name)
-    } else if name.contains("LGPLv3") {
-        String::from(GNU_LESSER_GENERAL_PUBLIC_LICENSE_V30)
-    } else if name.contains("GPLV3") {
-        String::from(GNU_GPL_V3)
+pub mod headers;
+
+/// license text with SPDX identifier, or identifier of custom license
+#[derive(Debug, Clone)]
+pub struct License {
+    pub id: Cow<'static, str>,
+    pub name: Cow<'static, str>,
+    text: Cow<'static, str>,
+    /// file or url of custom license, and None for embedded license
+    pub source: Option<String>,
+}
+
+const fn builtin(id: &'static str, name: &'static str, text: &'static str) -> License {
+    License {
+        id: Cow::Borrowed(id),
+        name: Cow::Borrowed(name),
+        text: Cow::Borrowed(text),
+        source: None,
+    }
+}
+
+/// embedded licenses in SPDX identifier order
+pub const LICENSES: &[License] = &[
+    builtin("0BSD", "BSD Zero Clause License", include_str!("0bsd.txt")),
+    builtin(
+        "AGPL-3.0-only",
+        "GNU Affero General Public License v3.0 only",
+        include_str!("agplv3.txt"),
+    ),
+    builtin(
+        "AGPL-3.0-or-later",
+        "GNU Affero General Public License v3.0 or later",
+        include_str!("agplv3.txt"),
+    ),
+    builtin(
+        "Apache-2.0",
+        "Apache License 2.0",
+        include_str!("apache2.txt"),
+    ),
+    builtin(
+        "BSD-2-Clause",
+        "BSD 2-Clause \"Simplified\" License",
+        include_str!("bsd2.txt"),
+    ),
+    builtin(
+        "BSD-3-Clause",
+        "BSD 3-Clause \"New\" or \"Revised\" License",
+        include_str!("bsd3.txt"),
+    ),
+    builtin(
+        "BSL-1.0",
+        "Boost Software License 1.0",
+        include_str!("bsl1.txt"),
+    ),
+    builtin(
+        "CC0-1.0",
+        "Creative Commons Zero v1.0 Universal",
+        include_str!("cc0.txt"),
+    ),
+    builtin(
+        "EPL-2.0",
+        "Eclipse Public License 2.0",
+        include_str!("epl2.txt"),
+    ),
+    builtin(
+        "GPL-2.0-only",
+        "GNU General Public License v2.0 only",
+        include_str!("gplv2.txt"),
+    ),
+    builtin(
+        "GPL-2.0-or-later",
+        "GNU General Public License v2.0 or later",
+        include_str!("gplv2.txt"),
+    ),
+    builtin(
+        "GPL-3.0-only",
+        "GNU General Public License v3.0 only",
+        include_str!("gplv3.txt"),
+    ),
+    builtin(
+        "GPL-3.0-or-later",
+        "GNU General Public License v3.0 or later",
+        include_str!("gplv3.txt"),
+    ),
+    builtin("ISC", "ISC License", include_str!("isc.txt")),
+    builtin(
+        "LGPL-2.1-only",
+        "GNU Lesser General Public License v2.1 only",
+        include_str!("lgplv21.txt"),
+    ),
+    builtin(
+        "LGPL-2.1-or-later",
+        "GNU Lesser General Public License v2.1 or later",
+        include_str!("lgplv21.txt"),
+    ),
+    builtin(
+        "LGPL-3.0-only",
+        "GNU Lesser General Public License v3.0 only",
+        include_str!("lgplv3.txt"),
+    ),
+    builtin(
+        "LGPL-3.0-or-later",
+        "GNU Lesser General Public License v3.0 or later",
+        include_str!("lgplv3.txt"),
+    ),
+    builtin("MIT", "MIT License", include_str!("mit.txt")),
+    builtin(
+        "MPL-2.0",
+        "Mozilla Public License 2.0",
+        include_str!("mozilla2.txt"),
+    ),
+    builtin("Unlicense", "The Unlicense", include_str!("unlicense.txt")),
+    builtin("Zlib", "zlib License", include_str!("zlib.txt")),
+];
+
+/// legacy license flags of `tgm license` and their SPDX identifiers
+pub const LEGACY_NAMES: &[(&str, &str)] = &[
+    ("apache2", "Apache-2.0"),
+    ("mit", "MIT"),
+    ("isc", "ISC"),
+    ("gplv3", "GPL-3.0-only"),
+    ("lgplv3", "LGPL-3.0-only"),
+    ("mozilla2", "MPL-2.0"),
+];
+
+// placeholders of copyright year and holder in copyright lines, such as MIT's `Copyright (c) [year] [fullname]`
+// and the notice of Apache's appendix
+const YEAR_PLACEHOLDERS: &[&str] = &["[yyyy]", "[year]"];
+const HOLDER_PLACEHOLDERS: &[&str] = &["[name of copyright owner]", "[fullname]"];
+// placeholders of "How to Apply These Terms" in GPL-family texts, which are replaced in license headers and NOTICE only
+const NOTICE_YEAR_PLACEHOLDERS: &[&str] = &["<year>"];
+const NOTICE_HOLDER_PLACEHOLDERS: &[&str] = &["<name of author>"];
+const PROJECT_PLACEHOLDERS: &[&str] = &[
+    "<one line to give the program's name and a brief idea of what it does.>",
+    "<one line to give the library's name and a brief idea of what it does.>",
+    "<program>",
+];
+
+/// find embedded or custom license by SPDX identifier case-insensitively, and legacy names such as `apache2` are accepted.
+/// Custom license with the same identifier takes precedence.
+pub fn find_license(id: &str) -> Option<License> {
+    let id = LEGACY_NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(id))
+        .map(|(_, spdx_id)| *spdx_id)
+        .unwrap_or(id);
+    custom_licenses(&paths::config_dir().join("licenses"))
+        .into_iter()
+        .chain(LICENSES.iter().cloned())
+        .find(|license| license.id.eq_ignore_ascii_case(id))
+}
+
+/// custom licenses from `<id>.txt` files in the directory, and name is the first line of text
+pub fn custom_licenses(dir: &Path) -> Vec<License> {
+    let mut licenses = vec![];
+    let Ok(entries) = fs::read_dir(dir) else {
+        return licenses;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("txt") {
+            continue;
+        }
+        let (Some(id), Ok(text)) = (path.file_stem(), fs::read_to_string(&path)) else {
+            continue;
+        };
+        licenses.push(License {
+            id: Cow::Owned(id.to_string_lossy().to_string()),
+            name: Cow::Owned(first_line(&text)),
+            text: Cow::Owned(text),
+            source: Some(path.display().to_string()),
+        });
+    }
+    licenses.sort_by(|a, b| a.id.cmp(&b.id));
+    licenses
+}
+
+/// licenses declared in registry index, and text is not fetched
+pub fn registry_licenses(source: &str) -> Result<Vec<License>, String> {
+    let index = RegistryIndex::load(source)?;
+    index
+        .licenses
+        .iter()
+        .map(|entry| {
+            let url = registry::resolve_url(source, &entry.url)
+                .map_err(|e| format!("Illegal url of license {}: {}", entry.id, e))?;
+            Ok(License {
+                id: Cow::Owned(entry.id.clone()),
+                name: Cow::Owned(entry.name.clone()),
+                text: Cow::Borrowed(""),
+                source: Some(url),
+            })
+        })
+        .collect()
+}
+
+/// find license in embedded, custom, then registries, and text of registry license is fetched
+pub fn resolve_license(id: &str, registries: &[String]) -> Result<License, String> {
+    if let Some(license) = find_license(id) {
+        return Ok(license);
+    }
+    for source in registries {
+        // unavailable registries are skipped, and `tgm registry check` reports them
+        let Ok(licenses) = registry_licenses(source) else {
+            continue;
+        };
+        if let Some(mut license) = licenses
+            .into_iter()
+            .find(|license| license.id.eq_ignore_ascii_case(id))
+        {
+            let url = license.source.clone().unwrap_or_default();
+            license.text = Cow::Owned(registry::read_source(&url)?);
+            return Ok(license);
+        }
+    }
+    Err(format!("Unknown license: {}", id))
+}
+
+/// embedded licenses, then custom licenses from tgm config directory and registries.
+/// Errors of unavailable registries are returned with licenses found.
+pub fn available_licenses(registries: &[String]) -> (Vec<License>, Vec<String>) {
+    let mut licenses = LICENSES.to_vec();
+    licenses.extend(custom_licenses(&paths::config_dir().join("licenses")));
+    let mut errors = vec![];
+    for source in registries {
+        match registry_licenses(source) {
+            Ok(registry_licenses) => licenses.extend(registry_licenses),
+            Err(e) => errors.push(e),
+        }
+    }
+    (licenses, errors)
+}
+
+fn first_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+impl License {
+    /// license text with copyright holder and current year in copyright line.
+    /// Embedded texts of GPL family are verbatim, and all placeholders of custom license are replaced with project name too.
+    pub fn render(&self, author_name: &str, project_name: &str) -> String {
+        let now: DateTime<Local> = Local::now();
+        self.render_with_year(&now.year().to_string(), author_name, project_name)
+    }
+
+    /// license text with the copyright year, such as fixed year of template tests
+    pub fn render_with_year(&self, year: &str, author_name: &str, project_name: &str) -> String {
+        if self.source.is_some() {
+            replace_placeholders(&self.text, year, author_name, project_name)
+        } else {
+            replace_copyright_placeholders(&self.text, year, author_name)
+        }
+    }
+}
+
+fn replace_copyright_placeholders(text: &str, year: &str, author_name: &str) -> String {
+    let mut text = text.to_string();
+    for (placeholders, value) in [
+        (YEAR_PLACEHOLDERS, year),
+        (HOLDER_PLACEHOLDERS, author_name),
+    ] {
+        for placeholder in placeholders {
+            text = text.replace(placeholder, value);
+        }
+    }
+    text
+}
+
+/// replace copyright placeholders, and placeholders of "How to Apply These Terms" for license headers and NOTICE
+fn replace_placeholders(text: &str, year: &str, author_name: &str, project_name: &str) -> String {
+    let mut text = replace_copyright_placeholders(text, year, author_name);
+    for (placeholders, value) in [
+        (NOTICE_YEAR_PLACEHOLDERS, year),
+        (NOTICE_HOLDER_PLACEHOLDERS, author_name),
+        (PROJECT_PLACEHOLDERS, project_name),
+    ] {
+        for placeholder in placeholders {
+            text = text.replace(placeholder, value);
+        }
+    }
+    text
+}
+
+/// tokens of SPDX license expression: parentheses, operators, license identifiers and exceptions.
+/// `MIT/Apache-2.0` of old Cargo.toml is `MIT OR Apache-2.0`.
+fn expression_tokens(expression: &str) -> Vec<String> {
+    expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ")
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+fn is_operator(token: &str) -> bool {
+    ["OR", "AND", "WITH"]
+        .iter()
+        .any(|operator| token.eq_ignore_ascii_case(operator))
+}
+
+/// licenses of SPDX license expression, such as `MIT OR Apache-2.0` for dual licensing and `MIT AND Apache-2.0`.
+/// Exception after `WITH`, such as `LLVM-exception`, is part of the expression, and its text is not included.
+pub fn parse_expression(expression: &str, registries: &[String]) -> Result<Vec<License>, String> {
+    let mut licenses: Vec<License> = vec![];
+    let mut tokens = expression_tokens(expression).into_iter();
+    while let Some(token) = tokens.next() {
+        if token.eq_ignore_ascii_case("WITH") {
+            tokens.next();
+            continue;
+        }
+        if token == "(" || token == ")" || is_operator(&token) {
+            continue;
+        }
+        let license = resolve_license(&token, registries)?;
+        if !licenses.iter().any(|item| item.id == license.id) {
+            licenses.push(license);
+        }
+    }
+    if licenses.is_empty() {
+        return Err(String::from("License is absent"));
+    }
+    Ok(licenses)
+}
+
+/// SPDX license expression with canonical identifiers of the licenses, and operators, parentheses and exceptions are kept,
+/// such as `mit AND (apache2 WITH LLVM-exception)` to `MIT AND (Apache-2.0 WITH LLVM-exception)`
+pub fn spdx_expression(expression: &str, licenses: &[License]) -> String {
+    let canonical_id = |token: &str| {
+        let id = LEGACY_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(token))
+            .map(|(_, spdx_id)| *spdx_id)
+            .unwrap_or(token);
+        licenses
+            .iter()
+            .find(|license| license.id.eq_ignore_ascii_case(id))
+            .map(|license| license.id.to_string())
+            .unwrap_or_else(|| token.to_string())
+    };
+    let mut spdx = String::new();
+    let mut is_exception = false;
+    for token in expression_tokens(expression) {
+        let token = if is_exception || token == "(" || token == ")" {
+            token
+        } else if is_operator(&token) {
+            token.to_uppercase()
+        } else {
+            canonical_id(&token)
+        };
+        is_exception = token == "WITH";
+        if !spdx.is_empty() && !spdx.ends_with('(') && token != ")" {
+            spdx.push(' ');
+        }
+        spdx.push_str(&token);
+    }
+    spdx
+}
+
+/// license file names: LICENSE for single license, otherwise suffixed by license family, such as LICENSE-MIT and LICENSE-APACHE.
+/// Full SPDX identifier is used as suffix if families are the same, such as GPL-2.0-only and GPL-3.0-only.
+pub fn license_file_names(licenses: &[License]) -> Vec<String> {
+    if licenses.len() == 1 {
+        return vec![String::from("LICENSE")];
+    }
+    let family = |license: &License| {
+        license
+            .id
+            .split('-')
+            .next()
+            .unwrap_or_default()
+            .to_uppercase()
+    };
+    licenses
+        .iter()
+        .map(|license| {
+            let suffix = family(license);
+            let is_unique = licenses
+                .iter()
+                .filter(|other| family(other) == suffix)
+                .count()
+                == 1;
+            if is_unique {
+                format!("LICENSE-{}", suffix)
+            } else {
+                format!("LICENSE-{}", license.id.to_uppercase())
+            }
+        })
+        .collect()
+}
+
+/// write LICENSE, or LICENSE-MIT and LICENSE-APACHE for `MIT OR Apache-2.0`, and existing license files are kept unless `force` is true.
+/// Output is license file path for single license, otherwise directory. Written files with SPDX identifiers are returned.
+pub fn write_license_files(
+    licenses: &[License],
+    output: &Path,
+    year: &str,
+    author_name: &str,
+    project_name: &str,
+    force: bool,
+) -> Result<Vec<(PathBuf, String)>, String> {
+    let license_files: Vec<PathBuf> = if licenses.len() == 1 && !output.is_dir() {
+        vec![output.to_path_buf()]
     } else {
-        String::new()
+        license_file_names(licenses)
+            .iter()
+            .map(|name| output.join(name))
+            .collect()
     };
+    if !force {
+        let license_dir = license_files[0].parent().unwrap_or(Path::new("."));
+        let mut existing_files: Vec<PathBuf> = ["LICENSE", "LICENSE.md", "LICENSE.txt", "COPYING"]
+            .iter()
+            .map(|name| license_dir.join(name))
+            .chain(license_files.iter().cloned())
+            .filter(|path| path.exists())
+            .collect();
+        existing_files.sort();
+        existing_files.dedup();
+        if !existing_files.is_empty() {
+            let existing_files: Vec<String> = existing_files
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(format!(
+                "License file exists: {}, please use --force to overwrite",
+                existing_files.join(", ")
+            ));
+        }
+    }
+    let mut written_files = vec![];
+    for (license, path) in licenses.iter().zip(license_files) {
+        write_file(
+            &path,
+            &license.render_with_year(year, author_name, project_name),
+        )?;
+        written_files.push((path, license.id.to_string()));
+    }
+    Ok(written_files)
+}
+
+/// write file, and existing file is kept unless `force` is true
+pub fn write_new_file(path: &Path, text: &str, force: bool) -> Result<(), String> {
+    if path.exists() && !force {
+        return Err(format!(
+            "File exists: {}, please use --force to overwrite",
+            path.display()
+        ));
+    }
+    write_file(path, text)
 }
 
-const APACHE_LICENSE_20: &str = include_str!("apache2.txt");
-const GNU_GPL_V3: &str = include_str!("gplv3.txt");
-const MIT: &str = include_str!("mit.txt");
-const ISC_LICENSE: &str = include_str!("isc.txt");
-const GNU_LESSER_GENERAL_PUBLIC_LICENSE_V30: &str = include_str!("lgplv3.txt");
-const MOZILLA_PUBLIC_LICENSE_20: &str = include_str!("mozilla2.txt");
+fn write_file(path: &Path, text: &str) -> Result<(), String> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).unwrap_or_default();
+    }
+    fs::write(path, text).map_err(|e| format!("Failed to create {}: {}", path.display(), e))
+}
+
+const DEFAULT_NOTICE: &str = "<program>
+Copyright [year] [fullname]
+
+This product includes software developed by [fullname].
+";
+
+/// NOTICE text from `NOTICE.txt` in tgm config directory, or default notice, with the same placeholders as licenses
+pub fn notice_text(author_name: &str, project_name: &str) -> String {
+    let notice = fs::read_to_string(paths::config_dir().join("NOTICE.txt"))
+        .unwrap_or_else(|_| DEFAULT_NOTICE.to_string());
+    let now: DateTime<Local> = Local::now();
+    replace_placeholders(&notice, &now.year().to_string(), author_name, project_name)
+}
+
+/// AUTHORS text: commit authors of git repository in the directory in order of first commit,
+/// or the author if not a git repository
+pub fn authors_text(dir: &Path, author: &str, project_name: &str) -> String {
+    let git_authors = Command::new("git")
+        .args(["log", "--reverse", "--format=%aN <%aE>"])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .unwrap_or_default();
+    let mut authors: Vec<&str> = vec![];
+    for line in git_authors.lines() {
+        if !authors.contains(&line) {
+            authors.push(line);
+        }
+    }
+    if authors.is_empty() {
+        authors.push(author);
+    }
+    format!("# Authors of {}\n\n{}\n", project_name, authors.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_licenses() {
+        paths::use_test_home();
+        assert_eq!(find_license("bsd-3-clause").unwrap().id, "BSD-3-Clause");
+        assert_eq!(find_license("lgplv3").unwrap().id, "LGPL-3.0-only");
+        assert!(find_license("GPL").is_none());
+        let lgpl = find_license("lgplv3").unwrap().render("Jane", "demo");
+        assert!(lgpl.starts_with("GNU LESSER GENERAL PUBLIC LICENSE"));
+        let apache = find_license("Apache-2.0").unwrap();
+        let text = apache.render_with_year("2020", "Jane", "demo");
+        assert!(text.contains("Copyright 2020 Jane"));
+        // GPL family is verbatim, and "How to Apply These Terms" is kept
+        let gpl = find_license("GPL-3.0-or-later").unwrap();
+        let text = gpl.render_with_year("2020", "Jane", "demo");
+        assert_eq!(text, gpl.text);
+        assert!(text.contains("    Copyright (C) <year>  <name of author>"));
+        let bsd = find_license("BSD-2-Clause").unwrap();
+        assert!(bsd
+            .render_with_year("2020", "Jane", "demo")
+            .contains("Copyright (c) 2020, Jane"));
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        paths::use_test_home();
+        let licenses = parse_expression("MIT OR Apache-2.0", &[]).unwrap();
+        assert_eq!(
+            license_file_names(&licenses),
+            vec!["LICENSE-MIT", "LICENSE-APACHE"]
+        );
+        let licenses = parse_expression("(GPL-2.0-only OR GPL-3.0-only)", &[]).unwrap();
+        assert_eq!(
+            license_file_names(&licenses),
+            vec!["LICENSE-GPL-2.0-ONLY", "LICENSE-GPL-3.0-ONLY"]
+        );
+        assert_eq!(
+            license_file_names(&parse_expression("mit", &[]).unwrap()),
+            vec!["LICENSE"]
+        );
+        assert!(parse_expression("MIT OR Foo", &[]).is_err());
+        let expression = "mit AND (apache2 WITH LLVM-exception)";
+        let licenses = parse_expression(expression, &[]).unwrap();
+        assert_eq!(licenses.len(), 2);
+        assert_eq!(
+            spdx_expression(expression, &licenses),
+            "MIT AND (Apache-2.0 WITH LLVM-exception)"
+        );
+        let licenses = parse_expression("MIT/Apache-2.0", &[]).unwrap();
+        assert_eq!(
+            spdx_expression("MIT/Apache-2.0", &licenses),
+            "MIT OR Apache-2.0"
+        );
+    }
+
+    #[test]
+    fn test_write_license_files() {
+        paths::use_test_home();
+        let dir = std::env::temp_dir().join(format!("tgm-license-files-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let licenses = parse_expression("MIT OR Apache-2.0", &[]).unwrap();
+        let written_files =
+            write_license_files(&licenses, &dir, "2020", "Jane", "demo", false).unwrap();
+        let existing = write_license_files(&licenses, &dir, "2020", "Jane", "demo", false);
+        let overwritten = write_license_files(&licenses, &dir, "2020", "Jane", "demo", true);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            written_files,
+            vec![
+                (dir.join("LICENSE-MIT"), "MIT".to_string()),
+                (dir.join("LICENSE-APACHE"), "Apache-2.0".to_string())
+            ]
+        );
+        assert!(existing.unwrap_err().starts_with("License file exists"));
+        assert!(overwritten.is_ok());
+    }
+
+    #[test]
+    fn test_custom_licenses() {
+        paths::use_test_home();
+        let licenses_dir =
+            std::env::temp_dir().join(format!("tgm-licenses-{}", std::process::id()));
+        fs::create_dir_all(&licenses_dir).unwrap();
+        let text = "ACME Proprietary License\n\nCopyright (c) [year] [fullname]\n";
+        fs::write(licenses_dir.join("ACME.txt"), text).unwrap();
+        let licenses = custom_licenses(&licenses_dir);
+        fs::remove_dir_all(&licenses_dir).unwrap();
+        assert_eq!(licenses[0].id, "ACME");
+        assert_eq!(licenses[0].name, "ACME Proprietary License");
+        assert!(licenses[0]
+            .render_with_year("2020", "ACME Inc.", "demo")
+            .ends_with("Copyright (c) 2020 ACME Inc.\n"));
+        assert!(notice_text("Jane", "demo").starts_with("demo\nCopyright "));
+    }
+}
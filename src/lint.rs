@@ -0,0 +1,397 @@
+//! template.json lint for template authors
+use crate::builtins::BUILTIN_NAMES;
+use crate::format::{find_file, FileFormat, TEMPLATE_FILE_NAMES};
+use crate::models::{
+    AppTemplate, APP_TEMPLATE_FIELDS, VARIABLE_FIELDS, VARIABLE_NAME_PATTERN, VARIABLE_TYPES,
+};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// lint issue with file and line location
+#[derive(Debug)]
+pub struct LintIssue {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// `@name@` placeholder in template files
+pub fn placeholder_regex() -> Regex {
+    Regex::new(r"@([A-Za-z_][A-Za-z0-9_]*)@").unwrap()
+}
+
+/// lint template directory with template.json, template.yaml, template.yml or template.toml
+pub fn lint_template(template_dir: &Path) -> Vec<LintIssue> {
+    let template_file = find_file(template_dir, TEMPLATE_FILE_NAMES)
+        .unwrap_or_else(|| template_dir.join("template.json"));
+    let file_name = template_file.file_name().unwrap().to_str().unwrap();
+    let format = FileFormat::from_path(file_name);
+    let mut issues: Vec<LintIssue> = vec![];
+    let mut issue = |file: &str, line: Option<usize>, message: String| {
+        issues.push(LintIssue {
+            file: file.to_string(),
+            line,
+            message,
+        });
+    };
+    let json_text = match fs::read_to_string(&template_file) {
+        Ok(text) => text,
+        Err(e) => {
+            issue(file_name, None, format!("failed to read: {}", e));
+            return issues;
+        }
+    };
+    let json: Value = match format {
+        FileFormat::Json => match serde_json::from_str(&json_text) {
+            Ok(json) => json,
+            Err(e) => {
+                issue(file_name, Some(e.line()), format!("illegal json: {}", e));
+                return issues;
+            }
+        },
+        _ => match format.parse(&json_text) {
+            Ok(json) => json,
+            Err(e) => {
+                let message = format!("illegal {}: {}", format.extension(), e);
+                issue(file_name, None, message);
+                return issues;
+            }
+        },
+    };
+    let Some(object) = json.as_object() else {
+        issue(
+            file_name,
+            Some(1),
+            String::from("template should be json object"),
+        );
+        return issues;
+    };
+    for key in object.keys() {
+        if !APP_TEMPLATE_FIELDS.contains(&key.as_str()) {
+            let line = find_key_line(&json_text, key);
+            issue(file_name, line, format!("unknown field '{}'", key));
+        }
+    }
+    for (i, variable) in object
+        .get("variables")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        for key in variable.as_object().into_iter().flat_map(|v| v.keys()) {
+            if !VARIABLE_FIELDS.contains(&key.as_str()) {
+                let line = find_key_line(&json_text, key);
+                issue(
+                    file_name,
+                    line,
+                    format!("unknown field '{}' in variables[{}]", key, i),
+                );
+            }
+        }
+    }
+    let app_template: AppTemplate = match serde_json::from_value(json) {
+        Ok(app_template) => app_template,
+        Err(e) => {
+            issue(file_name, None, format!("illegal template: {}", e));
+            return issues;
+        }
+    };
+    let variables = app_template.variables.unwrap_or_default();
+    let mut declared: HashSet<&str> = variables.iter().map(|v| v.name.as_str()).collect();
+    declared.extend(BUILTIN_NAMES);
+    let name_regex = Regex::new(VARIABLE_NAME_PATTERN).unwrap();
+    for variable in variables.iter() {
+        if !name_regex.is_match(&variable.name) {
+            let line = find_variable_line(&json_text, &variable.name);
+            let message = format!(
+                "illegal variable name '{}', and it should match with '{}'",
+                variable.name, VARIABLE_NAME_PATTERN
+            );
+            issue(file_name, line, message);
+        }
+        if !VARIABLE_TYPES.contains(&variable.type_name()) {
+            let line = find_variable_line(&json_text, &variable.name);
+            let message = format!(
+                "unknown type '{}' of variable '{}', and it should be one of {}",
+                variable.type_name(),
+                variable.name,
+                VARIABLE_TYPES.join(", ")
+            );
+            issue(file_name, line, message);
+        }
+        if let Some(pattern) = &variable.pattern {
+            if let Err(e) = Regex::new(pattern) {
+                let line = find_variable_line(&json_text, &variable.name);
+                let message = format!(
+                    "invalid regex pattern of variable '{}': {}",
+                    variable.name,
+                    e.to_string().lines().last().unwrap_or_default()
+                );
+                issue(file_name, line, message);
+            }
+        }
+        if let Some(Err(reason)) = variable
+            .value
+            .as_ref()
+            .map(|value| variable.validate(value))
+        {
+            let line = find_variable_line(&json_text, &variable.name);
+            let message = format!(
+                "default value '{}' of variable '{}' {}",
+                variable.display_value(variable.value.as_deref().unwrap_or_default()),
+                variable.name,
+                reason
+            );
+            issue(file_name, line, message);
+        }
+    }
+    let placeholder = placeholder_regex();
+    let mut referenced: HashSet<String> = HashSet::new();
+    for file in app_template.files.unwrap_or_default().iter() {
+        // placeholders in file path
+        for captures in placeholder.captures_iter(file) {
+            let name = &captures[1];
+            if !declared.contains(name) {
+                let line = find_value_line(&json_text, file);
+                let message = format!("placeholder '@{}@' has no declared variable", name);
+                issue(file_name, line, message);
+            }
+            referenced.insert(name.to_string());
+        }
+        let path = template_dir.join(file);
+        let Ok(text) = fs::read_to_string(&path) else {
+            let line = find_value_line(&json_text, file);
+            let message = if path.exists() {
+                format!("file '{}' is not a text file", file)
+            } else {
+                format!("file '{}' doesn't exist", file)
+            };
+            issue(file_name, line, message);
+            continue;
+        };
+        for (line_number, line) in (1..).zip(text.lines()) {
+            for captures in placeholder.captures_iter(line) {
+                let name = &captures[1];
+                if !declared.contains(name) {
+                    let message = format!("placeholder '@{}@' has no declared variable", name);
+                    issue(file, Some(line_number), message);
+                }
+                referenced.insert(name.to_string());
+            }
+        }
+    }
+    for variable in variables.iter() {
+        if !referenced.contains(&variable.name) {
+            let line = find_variable_line(&json_text, &variable.name);
+            let message = format!("variable '{}' is never referenced in files", variable.name);
+            issue(file_name, line, message);
+        }
+    }
+    issues
+}
+
+/// 1-based line number of the first line containing the needle
+fn find_line(text: &str, needle: &str) -> Option<usize> {
+    (1..)
+        .zip(text.lines())
+        .find(|(_, line)| line.contains(needle))
+        .map(|(line_number, _)| line_number)
+}
+
+/// line of `key:` or `key =` in template file
+fn find_key_line(text: &str, key: &str) -> Option<usize> {
+    let regex = Regex::new(&format!(
+        r#"(^|[\s{{,\-])["']?{}["']?\s*[:=]"#,
+        regex::escape(key)
+    ))
+    .ok()?;
+    find_regex_line(text, &regex)
+}
+
+/// line of the value, such as file name, in template file
+fn find_value_line(text: &str, value: &str) -> Option<usize> {
+    find_line(text, &format!("\"{}\"", value))
+        .or_else(|| find_line(text, &format!("'{}'", value)))
+        .or_else(|| find_line(text, value))
+}
+
+/// line of `"name": "{variable}"`, `name: {variable}` or `name = "{variable}"` in template file
+fn find_variable_line(text: &str, variable_name: &str) -> Option<usize> {
+    let regex = Regex::new(&format!(
+        r#"["']?name["']?\s*[:=]\s*["']?{}(["',\s}}]|$)"#,
+        regex::escape(variable_name)
+    ))
+    .ok()?;
+    find_regex_line(text, &regex)
+}
+
+fn find_regex_line(text: &str, regex: &Regex) -> Option<usize> {
+    (1..)
+        .zip(text.lines())
+        .find(|(_, line)| regex.is_match(line))
+        .map(|(line_number, _)| line_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_template() {
+        let template_dir = std::env::temp_dir().join(format!("tgm-lint-{}", std::process::id()));
+        fs::create_dir_all(&template_dir).unwrap();
+        let template_json = r#"{
+  "name": "demo",
+  "repository": "https://github.com/tgm-templates/demo",
+  "description": "demo",
+  "author": "linux_china",
+  "variables": [
+    {
+      "name": "groupId",
+      "description": "Maven groupId",
+      "pattern": "[a-z"
+    },
+    {
+      "name": "artifactId",
+      "description": "Maven artifactId",
+      "value": "demo",
+      "choices": ["app", "lib"]
+    }
+  ],
+  "files": ["pom.xml", "README.md"]
+}"#;
+        fs::write(template_dir.join("template.json"), template_json).unwrap();
+        fs::write(
+            template_dir.join("pom.xml"),
+            "<project>\n  <groupId>@groupId@</groupId>\n  <version>@version@</version>\n</project>\n",
+        )
+        .unwrap();
+        let issues: Vec<String> = lint_template(&template_dir)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        fs::remove_file(template_dir.join("template.json")).unwrap();
+        let template_yaml = r#"name: demo
+repository: https://github.com/tgm-templates/demo
+description: demo
+variables:
+  - name: groupId
+    description: Maven groupId
+  - name: version
+    description: version
+    default: "1.0"
+  - name: app-name
+    description: app name
+    type: int
+files:
+  - pom.xml
+"#;
+        fs::write(template_dir.join("template.yaml"), template_yaml).unwrap();
+        let yaml_issues: Vec<String> = lint_template(&template_dir)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        fs::remove_dir_all(&template_dir).unwrap();
+        assert_eq!(
+            yaml_issues,
+            vec![
+                "template.yaml:9: unknown field 'default' in variables[1]",
+                "template.yaml:10: illegal variable name 'app-name', and it should match with '^[A-Za-z_][A-Za-z0-9_]*$'",
+                "template.yaml:10: unknown type 'int' of variable 'app-name', and it should be one of string, number, boolean",
+                "template.yaml:10: variable 'app-name' is never referenced in files",
+            ]
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "template.json:5: unknown field 'author'",
+                "template.json:8: invalid regex pattern of variable 'groupId': error: unclosed character class",
+                "template.json:13: default value 'demo' of variable 'artifactId' should be one of app, lib",
+                "pom.xml:3: placeholder '@version@' has no declared variable",
+                "template.json:19: file 'README.md' doesn't exist",
+                "template.json:13: variable 'artifactId' is never referenced in files",
+            ]
+        );
+    }
+
+    /// schemas/template.schema.json documents template.json, and must match the fields and rules of lint
+    #[test]
+    fn test_schema_in_sync() {
+        let schema: Value =
+            serde_json::from_str(include_str!("../schemas/template.schema.json")).unwrap();
+        let keys = |value: &Value| {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        let sorted = |fields: &[&str]| {
+            let mut fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+            fields.sort();
+            fields
+        };
+        let variable_schema = &schema["$defs"]["variable"];
+        assert_eq!(keys(&schema["properties"]), sorted(APP_TEMPLATE_FIELDS));
+        assert_eq!(
+            keys(&variable_schema["properties"]),
+            sorted(VARIABLE_FIELDS)
+        );
+        assert_eq!(
+            variable_schema["properties"]["type"]["enum"],
+            serde_json::json!(VARIABLE_TYPES)
+        );
+        assert_eq!(
+            variable_schema["properties"]["name"]["pattern"],
+            VARIABLE_NAME_PATTERN
+        );
+        assert_eq!(
+            schema["properties"]["schema_version"]["maximum"],
+            crate::registry::TEMPLATE_SCHEMA_VERSION
+        );
+        // required fields of schema are required by serde too
+        let template =
+            serde_json::json!({"name": "demo", "repository": "demo", "description": "demo"});
+        let variable = serde_json::json!({"name": "demo", "description": "demo"});
+        assert_required::<AppTemplate>(&schema, &template);
+        assert_required::<crate::models::Variable>(variable_schema, &variable);
+    }
+
+    /// required fields of schema are exactly the fields required by serde
+    fn assert_required<T: serde::de::DeserializeOwned>(schema: &Value, value: &Value) {
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field.as_str().unwrap())
+            .collect();
+        let mut fields: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort();
+        let mut sorted_required = required.clone();
+        sorted_required.sort();
+        assert_eq!(fields, sorted_required);
+        assert!(serde_json::from_value::<T>(value.clone()).is_ok());
+        for field in required {
+            let mut value = value.clone();
+            value.as_object_mut().unwrap().remove(field);
+            assert!(serde_json::from_value::<T>(value).is_err(), "{}", field);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! template lookup in local settings, registries, then GitHub organization of central
+use crate::format::TEMPLATE_FILE_NAMES;
+use crate::github::GithubApi;
+use crate::models::{AppTemplate, GithubRepo, Settings, Template};
+use crate::paths;
+use crate::registry::{RegistryEntry, RegistryIndex};
+use crate::search::Candidate;
+use crate::source::TemplateSource;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// cached template.json is fetched again after one day
+const TEMPLATE_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// GitHub organization of remote templates, `tgm-templates` by default
+pub fn central(settings: &Settings) -> String {
+    settings
+        .central
+        .clone()
+        .unwrap_or_else(|| String::from("tgm-templates"))
+}
+
+/// find template from local settings, registries, then https://github.com/tgm-templates/.
+/// Unavailable registries are skipped, and their errors are reported if template not found.
+pub fn find_template(settings: &Settings, template_name: &str) -> Result<Template, String> {
+    if let Some(template) = settings.find_template(template_name) {
+        return Ok(template.clone());
+    }
+    let mut errors = vec![];
+    for source in settings.registries.iter().flatten() {
+        match RegistryIndex::load(source) {
+            Ok(index) => {
+                if let Some(entry) = index.find_template(template_name) {
+                    return Ok(entry.to_template());
+                }
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    let api = GithubApi::from_settings(settings);
+    match GithubRepo::fetch_tgm_template_repos(&api, &central(settings)) {
+        Ok(repos) => {
+            if let Some(repo) = repos.iter().find(|repo| repo.name == template_name) {
+                return Ok(repo.to_template());
+            }
+        }
+        Err(e) => errors.push(e),
+    }
+    if errors.is_empty() {
+        Err(format!("Template not found: {}", template_name))
+    } else {
+        Err(format!(
+            "Template not found: {} ({})",
+            template_name,
+            errors.join("; ")
+        ))
+    }
+}
+
+/// templates from local settings, and from registries and GitHub if remote.
+/// Errors of unavailable registries and GitHub are returned with templates found.
+pub fn candidates(settings: &Settings, remote: bool) -> (Vec<Candidate>, Vec<String>) {
+    let mut candidates: Vec<Candidate> = settings
+        .templates
+        .iter()
+        .map(|template| Candidate {
+            source: String::from("local"),
+            template: template.clone(),
+        })
+        .collect();
+    let mut errors = vec![];
+    if remote {
+        for source in settings.registries.iter().flatten() {
+            match RegistryIndex::load(source) {
+                Ok(index) => {
+                    candidates.extend(index.templates.iter().map(|entry| Candidate {
+                        source: source.clone(),
+                        template: entry.to_template(),
+                    }));
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        let api = GithubApi::from_settings(settings);
+        match GithubRepo::fetch_tgm_template_repos(&api, &central(settings)) {
+            Ok(repos) => {
+                candidates.extend(repos.iter().filter(|repo| !repo.name.starts_with(".")).map(
+                    |repo| Candidate {
+                        source: String::from("remote"),
+                        template: repo.to_template(),
+                    },
+                ));
+            }
+            Err(e) => errors.push(format!("Failed to fetch remote templates: {}", e)),
+        }
+    }
+    (candidates, errors)
+}
+
+/// remote template from GitHub or registry index
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum RemoteTemplate {
+    Github(GithubRepo),
+    Registry(RegistryEntry),
+}
+
+/// templates from GitHub organization of central, then registries.
+/// Errors of unavailable registries and GitHub are returned with templates found.
+pub fn remote_templates(settings: &Settings) -> (Vec<RemoteTemplate>, Vec<String>) {
+    let mut remote_templates = vec![];
+    let mut errors = vec![];
+    let api = GithubApi::from_settings(settings);
+    match GithubRepo::fetch_tgm_template_repos(&api, &central(settings)) {
+        Ok(repos) => {
+            // ignore repo name starts with ".", such as '.github'
+            remote_templates.extend(
+                repos
+                    .into_iter()
+                    .filter(|repo| !repo.name.starts_with("."))
+                    .map(RemoteTemplate::Github),
+            );
+        }
+        Err(e) => errors.push(format!("Failed to fetch remote templates: {}", e)),
+    }
+    for source in settings.registries.iter().flatten() {
+        match RegistryIndex::load(source) {
+            Ok(index) => {
+                remote_templates.extend(index.templates.into_iter().map(RemoteTemplate::Registry))
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    (remote_templates, errors)
+}
+
+/// template name, and not local path, url or repository, such as `spring-boot-java`
+pub fn is_template_name(name: &str) -> bool {
+    !Path::new(name).exists()
+        && !name.contains("://")
+        && !name.contains('/')
+        && !name.starts_with("git@")
+}
+
+/// load template.json from template source, and template with repository, ref and sub directory
+pub fn load_source(
+    settings: &Settings,
+    source: TemplateSource,
+) -> Result<(AppTemplate, Template), String> {
+    let (app_template, repo_source) = match source {
+        TemplateSource::Local(path) => (AppTemplate::from_file(&path)?, None),
+        TemplateSource::Url(url) => (fetch_app_template(&url)?, None),
+        TemplateSource::Repository(repo_source) => {
+            let git_ref = repo_source.resolve_ref(&GithubApi::from_settings(settings));
+            // template.json first, then template.yaml, template.yml and template.toml
+            let mut errors = vec![];
+            let app_template = TEMPLATE_FILE_NAMES
+                .iter()
+                .find_map(|file_name| {
+                    fetch_app_template(&repo_source.raw_url(&git_ref, file_name))
+                        .map_err(|e| errors.push(e))
+                        .ok()
+                })
+                .ok_or_else(|| errors.remove(0))?;
+            (app_template, Some(repo_source))
+        }
+    };
+    let template = Template {
+        name: app_template.name.clone(),
+        repository: repo_source
+            .as_ref()
+            .map(|repo_source| repo_source.repository_url())
+            .unwrap_or_else(|| app_template.repository.clone()),
+        git_ref: repo_source.as_ref().and_then(|repo| repo.git_ref.clone()),
+        subdir: repo_source.as_ref().and_then(|repo| repo.subdir.clone()),
+        description: app_template.description.clone(),
+        tags: app_template.tags.clone().unwrap_or_default(),
+    };
+    Ok((app_template, template))
+}
+
+fn fetch_app_template(url: &str) -> Result<AppTemplate, String> {
+    AppTemplate::with_remote(url)
+        .map_err(|e| format!("Failed to load template from {}: {}", url, e))
+}
+
+/// load template.json from local path, url, or repository of template found by name.
+/// template.json of template found by name is cached in cache directory, and `refresh` skips the cache.
+pub fn load_app_template(
+    settings: &Settings,
+    name: &str,
+    refresh: bool,
+) -> Result<(AppTemplate, Template), String> {
+    if is_template_name(name) {
+        if let Ok(template) = find_template(settings, name) {
+            let cache_file = template_cache_file(&template);
+            if !refresh {
+                if let Some(app_template) = read_template_cache(&cache_file) {
+                    return Ok((app_template, template));
+                }
+            }
+            let repo_source = template.repo_source().ok_or(format!(
+                "Failed to locate template.json in {}",
+                template.repository
+            ))?;
+            let (app_template, _) = load_source(settings, TemplateSource::Repository(repo_source))?;
+            // cache is best effort, and template is fetched again if cache absent
+            if let Ok(json_text) = serde_json::to_string_pretty(&app_template) {
+                cache_file
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&cache_file, json_text))
+                    .unwrap_or_default();
+            }
+            return Ok((app_template, template));
+        }
+    }
+    load_source(settings, TemplateSource::parse(name)?)
+}
+
+/// cache file of template.json by repository, ref and sub directory of template,
+/// such as `templates/https___github.com_tgm-templates_demo@main.json` in cache directory
+pub fn template_cache_file(template: &Template) -> PathBuf {
+    let mut key = template.repository.clone();
+    if let Some(git_ref) = &template.git_ref {
+        key.push('@');
+        key.push_str(git_ref);
+    }
+    if let Some(subdir) = &template.subdir {
+        key.push('#');
+        key.push_str(subdir);
+    }
+    let file_name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-.@#".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    paths::cache_dir()
+        .join("templates")
+        .join(format!("{}.json", file_name))
+}
+
+/// cached template.json, and None if absent, expired or illegal
+fn read_template_cache(cache_file: &Path) -> Option<AppTemplate> {
+    let modified = fs::metadata(cache_file).ok()?.modified().ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    if age > TEMPLATE_CACHE_TTL {
+        return None;
+    }
+    serde_json::from_str(&fs::read_to_string(cache_file).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_template() {
+        let settings = Settings {
+            templates: vec![Template {
+                name: "demo".to_string(),
+                repository: "https://github.com/tgm-templates/demo".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(central(&settings), "tgm-templates");
+        let template = find_template(&settings, "demo").unwrap();
+        assert_eq!(template.repository, "https://github.com/tgm-templates/demo");
+        let (candidates, _) = candidates(&settings, false);
+        assert_eq!(candidates.len(), 1);
+        assert!(is_template_name("demo"));
+        assert!(!is_template_name("linux-china/demo"));
+        assert!(!is_template_name("https://example.com/template.json"));
+    }
+
+    #[test]
+    fn test_template_cache() {
+        paths::use_test_home();
+        let settings = Settings {
+            templates: vec![Template {
+                name: "cached-demo".to_string(),
+                repository: "https://github.com/tgm-templates/cached-demo".to_string(),
+                git_ref: Some("main".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let cache_file = template_cache_file(&settings.templates[0]);
+        assert_eq!(
+            cache_file.file_name().unwrap(),
+            "https___github.com_tgm-templates_cached-demo@main.json"
+        );
+        let app_template = AppTemplate {
+            name: "cached-demo".to_string(),
+            description: "cached".to_string(),
+            ..Default::default()
+        };
+        fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
+        fs::write(&cache_file, serde_json::to_string(&app_template).unwrap()).unwrap();
+        let (cached, template) = load_app_template(&settings, "cached-demo", false).unwrap();
+        assert_eq!(cached.description, "cached");
+        assert_eq!(template.git_ref.as_deref(), Some("main"));
+        fs::remove_file(&cache_file).unwrap();
+    }
+}
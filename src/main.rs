@@ -3,196 +3,282 @@
 #![doc(html_logo_url = "https://avatars2.githubusercontent.com/u/71794210?s=200&v=4")]
 
 mod app;
-mod licenses;
-mod models;
-
-use crate::app::build_app;
-use crate::licenses::get_license;
-use crate::models::{AppTemplate, GithubRepo, Settings, Variable};
-use chrono::{DateTime, Datelike, Local};
-use clap_complete::shells::{Bash, Zsh};
+mod completion;
+mod output;
+mod prompt;
+
+use crate::app::{App, Cli, CompleteArgs, ConfigArgs, ConfigCommand, LicenseArgs, RegistryCommand};
+use crate::output::OutputFormat;
+use clap::{CommandFactory, Parser};
 use colored::*;
-use regex::Regex;
 use std::collections::HashMap;
-use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::process::Stdio;
 use std::{env, fs};
+use tgm::licenses::headers::{self, HeaderStatus};
+use tgm::models::{AppTemplate, Settings, Template, Variable};
+use tgm::registry::RegistryIndex;
+use tgm::search::Candidate;
+use tgm::source::TemplateSource;
+use tgm::Generator;
+use tgm::{
+    builtins, format, harness, licenses, lint, lookup, paths, registry, scaffold, search, secret,
+};
 
 fn main() {
-    let app = build_app();
-    let matches = app.get_matches();
-    if matches.subcommand().is_none() {
+    // dynamic shell completion, such as `COMPLETE=fish tgm`
+    clap_complete::CompleteEnv::with_factory(App::command)
+        .var(completion::COMPLETE_VAR)
+        .complete();
+    let app = App::parse();
+    let Some(command) = app.command else {
         println!(
             "{}",
             "😂 Please use subcommand or --help to display help!".red()
         );
         return;
+    };
+    if let Some(config_file) = app.config {
+        paths::set_config_file(config_file);
     }
-    let settings = Settings::load();
-    let (sub_command, args) = matches.subcommand().unwrap();
-    if sub_command == "list" {
-        if args.get_flag("remote") {
-            list_remote_templates(&settings);
-        } else {
-            list_templates(&settings);
+    let settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", format!("😂 {}", e).red());
+            std::process::exit(1);
         }
-    } else if sub_command == "config" {
-        if args.get_flag("edit") {
-            config_global_variables();
-        } else {
-            show_global_variables(&settings);
-        }
-    } else if sub_command == "license" {
-        let anonymous = "Anonymous".to_owned();
-        let author_name = args.get_one::<String>("author").unwrap_or(&anonymous);
-        let license_type = if args.get_flag("apache2") {
-            "apache2"
-        } else if args.get_flag("mit") {
-            "mit"
-        } else if args.get_flag("isc") {
-            "isc"
-        } else if args.get_flag("gplv3") {
-            "gplv3"
-        } else if args.get_flag("lgplv3") {
-            "lgplv3"
-        } else if args.get_flag("mozilla2") {
-            "mozilla2"
-        } else {
-            ""
-        };
-        if license_type.is_empty() {
-            println!("{}", "Unknown license type".red());
-            return;
-        }
-        let license_text = get_license(license_type, author_name);
-        let mut license_file = File::create(Path::new("LICENSE")).unwrap();
-        license_file.write_all(license_text.as_bytes()).unwrap();
-        println!("📄 LICENSE file created!")
-    } else if sub_command == "complete" {
-        if args.get_flag("zsh") {
-            clap_complete::generate(Zsh, &mut build_app(), "tgm", &mut std::io::stdout());
-        } else if args.get_flag("bash") {
-            clap_complete::generate(Bash, &mut build_app(), "tgm", &mut std::io::stdout());
-        } else if args.get_flag("oh_my_zsh") {
-            let home = env::var("HOME").unwrap();
-            let dest_dir = format!("{}/.oh-my-zsh/custom/plugins/tgm", home);
-            let result = fs::create_dir_all(Path::new(&dest_dir));
-            if result.is_ok() {
-                // write _tgm file to plugin directory
-                let dest_file = format!("{}/_tgm", dest_dir);
-                let mut file = File::create(dest_file).unwrap();
-                clap_complete::generate(Zsh, &mut build_app(), "tgm", &mut file);
-                // read .zshrc add enable tgm plugin
-                let zshrc_dest = format!("{}/.zshrc", home);
-                let zshrc_text =
-                    fs::read_to_string(Path::new(&zshrc_dest)).expect("Failed to read ~/.zshrc");
-                if !zshrc_text.contains("(tgm ") && !zshrc_text.contains(" tgm ") {
-                    let new_zshrc_text = zshrc_text.replacen("plugins=(", "plugins=(tgm ", 3);
-                    let mut zshrc_file = File::create(Path::new(&zshrc_dest)).unwrap();
-                    zshrc_file.write_all(new_zshrc_text.as_bytes()).unwrap();
-                }
-                println!("💯 tgm for oh-my-zsh installed successfully! Please open a new console tab to make it work.")
-            } else {
-                println!("Failed to create directory: {}", &dest_dir);
-            }
+    };
+    // `info --json` is a deprecated alias of `--output json`
+    let json_alias = matches!(command, Cli::Info { json: true, .. });
+    let output = app
+        .output
+        .or(json_alias.then_some(OutputFormat::Json))
+        .unwrap_or(OutputFormat::Table);
+    if !output.is_table() {
+        colored::control::set_override(false);
+    }
+    if let Err(e) = run(command, &settings, output) {
+        print_error(output, &format!("😂 {}", e));
+        std::process::exit(1);
+    }
+}
+
+/// run sub command, and error is printed by main with exit code 1
+fn run(command: Cli, settings: &Settings, output: OutputFormat) -> Result<(), String> {
+    match command {
+        Cli::List { remote: true, .. } => list_remote_templates(settings, output),
+        Cli::List { .. } => list_templates(settings, output),
+        Cli::Search {
+            query, tag, local, ..
+        } => search_templates(
+            settings,
+            query.as_deref().unwrap_or_default(),
+            &tag,
+            !local,
+            output,
+        ),
+        Cli::Config(args) => config(settings, args, output),
+        Cli::License(args) => license(settings, args),
+        Cli::Complete(args) => complete(args),
+        Cli::Add {
+            name,
+            repo,
+            desc,
+            tag,
+        } => add_template(Template {
+            name,
+            repository: repo,
+            description: desc,
+            tags: tag,
+            ..Default::default()
+        }),
+        Cli::Import { name } => import_template(settings, &name),
+        Cli::Info { name, refresh, .. } => show_template_info(settings, &name, refresh, output),
+        Cli::Lint { dir } => lint_template(&dir.unwrap_or_else(|| PathBuf::from("."))),
+        Cli::Test { dir, update } => {
+            test_template(&dir.unwrap_or_else(|| PathBuf::from(".")), update)
         }
-    } else if sub_command == "add" {
-        let name = args.get_one::<String>("name").unwrap();
-        let repo = args.get_one::<String>("repo").unwrap();
-        let desc = args.get_one::<String>("desc").unwrap();
-        add_template(name, repo, desc);
-    } else if sub_command == "import" {
-        let mut url = String::from(args.get_one::<String>("name").unwrap());
-        if !(url.starts_with("http://") || url.starts_with("https://")) {
-            // github template repository
-            if !url.contains("/") {
-                // template from https://github.com/tgm-templates/
-                url = format!("tgm-templates/{}", url);
-            }
-            url = format!(
-                "https://raw.githubusercontent.com/{}/master/template.json",
-                url
-            );
-        } else {
-            println!(
-                "{}",
-                "😂 Repository's url should start with http:// or https://".red()
+        Cli::InitTemplate { dir, var, yes } => init_template(&dir, &var, yes),
+        Cli::Registry { command } => manage_registry(command),
+        Cli::Remove { name } => delete_template(&name),
+        Cli::Create { name, dir } => create(settings, name, dir),
+    }
+}
+
+fn config(settings: &Settings, args: ConfigArgs, output: OutputFormat) -> Result<(), String> {
+    if let Some(command) = args.command {
+        manage_config(settings, command, output)
+    } else if args.edit {
+        config_global_variables()
+    } else if let Some(format) = args.convert {
+        convert_settings(format)
+    } else if args.show_origin {
+        show_settings_origin(settings, output)
+    } else {
+        show_global_variables(settings, output)
+    }
+}
+
+fn license(settings: &Settings, args: LicenseArgs) -> Result<(), String> {
+    let registries = settings.registries.clone().unwrap_or_default();
+    if args.list {
+        return list_licenses(&registries);
+    }
+    let license_id = args.license_id();
+    if license_id.is_none() && !args.notice && !args.authors {
+        return Err(
+            "Please supply SPDX license identifier, and use --list to show available licenses"
+                .to_string(),
+        );
+    }
+    let author_name = &args
+        .author
+        .or_else(|| settings.find_variable_value("author_name"))
+        .unwrap_or_else(|| "Anonymous".to_owned());
+    let project_name = args.project.unwrap_or_else(current_dir_name);
+    let dest = args.dest;
+    let force = args.force;
+    if let Some(license_id) = &license_id {
+        if args.headers {
+            let dir = args.dir.unwrap_or_else(|| PathBuf::from("."));
+            return update_license_headers(
+                &dir,
+                license_id,
+                &registries,
+                author_name,
+                &project_name,
+                args.full,
+                args.check,
             );
         }
-        if !url.ends_with("/template.json") {
-            url = format!("{}/template.json", url);
+        let licenses = licenses::parse_expression(license_id, &registries)?;
+        let license_dest = dest.as_deref().unwrap_or(Path::new("."));
+        let year = chrono::Local::now().format("%Y").to_string();
+        let license_files = licenses::write_license_files(
+            &licenses,
+            license_dest,
+            &year,
+            author_name,
+            &project_name,
+            force,
+        )?;
+        for (path, license_id) in license_files {
+            println!("📄 {} file created with {}", path.display(), license_id);
         }
-        match AppTemplate::with_remote(&url) {
-            Ok(app_template) => {
-                add_template(
-                    &app_template.name,
-                    &app_template.repository,
-                    &app_template.description,
-                );
-            }
-            Err(_e) => {
-                println!(
-                    "{}",
-                    format!(
-                        "Failed to load template from {}, please check the json data!",
-                        url
-                    )
-                        .as_str()
-                        .red()
-                );
+    }
+    // NOTICE and AUTHORS are saved in the directory of license files
+    let output_dir = match dest {
+        Some(path) if path.is_dir() || license_id.is_none() => path,
+        Some(path) => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        None => PathBuf::from("."),
+    };
+    if args.notice {
+        let text = licenses::notice_text(author_name, &project_name);
+        write_new_file(&output_dir.join("NOTICE"), &text, force)?;
+    }
+    if args.authors {
+        let author = match settings.find_variable_value("author_email") {
+            Some(author_email) => format!("{} <{}>", author_name, author_email),
+            None => author_name.to_string(),
+        };
+        let text = licenses::authors_text(&output_dir, &author, &project_name);
+        write_new_file(&output_dir.join("AUTHORS"), &text, force)?;
+    }
+    Ok(())
+}
+
+fn complete(args: CompleteArgs) -> Result<(), String> {
+    if let Some(shell) = args.shell() {
+        return completion::write_script(shell, args.dynamic, &mut std::io::stdout());
+    }
+    if !args.oh_my_zsh {
+        return Err("Please supply shell, such as --zsh, --bash or --oh_my_zsh".to_string());
+    }
+    let home = paths::home_dir().ok_or("Home directory not found!")?;
+    if args.uninstall {
+        let plugin_dir = completion::uninstall_oh_my_zsh(&home)?;
+        println!(
+            "💯 tgm for oh-my-zsh uninstalled, and {} removed!",
+            plugin_dir.display()
+        );
+    } else {
+        let plugin_dir = completion::install_oh_my_zsh(&home)?;
+        println!("💯 tgm for oh-my-zsh installed successfully in {}! Please open a new console tab to make it work.", plugin_dir.display());
+    }
+    Ok(())
+}
+
+/// create app, and template and app's directory are prompted on terminal if absent
+fn create(settings: &Settings, name: Option<String>, dir: Option<String>) -> Result<(), String> {
+    let template_name = match name {
+        Some(name) => name,
+        None if prompt::is_interactive() => {
+            let candidates = collect_candidates(settings, true);
+            match prompt::pick_template(&candidates)? {
+                Some(name) => name,
+                None => return Ok(()),
             }
         }
-    } else if sub_command == "remove" {
-        let name = args.get_one::<String>("name").unwrap();
-        delete_template(name);
-    } else if sub_command == "create" {
-        let template_name = args.get_one::<String>("name").unwrap();
-        let app_dir = args.get_one::<String>("dir").unwrap();
-        let current_dir = String::from(env::current_dir().unwrap().to_str().unwrap());
-        let dest_dir = format!("{}/{}", current_dir, app_dir);
-        let dest_path = Path::new(&dest_dir);
-        if dest_path.exists() {
-            println!("😂  '{}' directory exits already!", app_dir);
-            return;
-        }
-        create_app(template_name, &current_dir, app_dir, &settings);
-        //check app created or not
-        if dest_path.exists() {
-            println!(
-                "{}",
-                format!("💯 App created successfully under {} directory!", app_dir)
-                    .as_str()
-                    .green()
-            );
-        }
+        None => return Err("Please supply template name and app's directory".to_string()),
+    };
+    let app_dir = match dir {
+        Some(dir) => dir,
+        None if prompt::is_interactive() => prompt::input_app_dir(&template_name)?,
+        None => return Err("Please supply app's directory".to_string()),
+    };
+    let dest_dir = env::current_dir().unwrap().join(&app_dir);
+    if dest_dir.exists() {
+        return Err(format!("'{}' directory exits already!", app_dir));
+    }
+    create_app(&template_name, &dest_dir, settings)?;
+    println!(
+        "{}",
+        format!("💯 App created successfully under {} directory!", app_dir)
+            .as_str()
+            .green()
+    );
+    Ok(())
+}
+
+fn add_template(template: Template) -> Result<(), String> {
+    let mut settings = Settings::load_user()?;
+    let name = template.name.clone();
+    if settings.add_template(template)? {
+        println!("{} template added!", name);
     } else {
-        let hint = format!("😂 Unknown sub command: {}", sub_command);
-        println!("{}", hint.as_str().red());
+        println!("{} template already exits!", name);
     }
+    Ok(())
 }
 
-fn add_template(name: &str, url: &str, description: &str) {
-    let mut settings = Settings::load();
-    settings.add_template(name.into(), url.into(), description.into());
+/// import template from template.json in repository, url or local path
+fn import_template(settings: &Settings, name: &str) -> Result<(), String> {
+    let (_, template) = lookup::load_source(settings, TemplateSource::parse(name)?)?;
+    add_template(template)
 }
 
-fn delete_template(name: &str) {
-    let mut settings = Settings::load();
-    settings.delete_template(name);
+fn delete_template(name: &str) -> Result<(), String> {
+    let mut settings = Settings::load_user()?;
+    if settings.delete_template(name)? {
+        println!("{} template removed!", name);
+    } else {
+        println!("{} template not found!", name);
+    }
+    Ok(())
 }
 
-fn list_templates(settings: &Settings) {
-    if settings.templates.is_empty() {
+fn list_templates(settings: &Settings, output: OutputFormat) -> Result<(), String> {
+    if !output.is_table() {
+        output.print(&settings.templates);
+    } else if settings.templates.is_empty() {
         println!(
             "No template available! Please use '{}' commands to add new template.",
             "add or import".green()
         );
     } else {
-        let mut i = 1;
-        for template in settings.templates.iter() {
+        for (i, template) in (1..).zip(settings.templates.iter()) {
             println!(
                 "{}. {} - {} : {}",
                 i,
@@ -200,59 +286,475 @@ fn list_templates(settings: &Settings) {
                 template.repository,
                 template.description
             );
-            i = i + 1;
         }
     }
+    Ok(())
+}
+
+fn list_remote_templates(settings: &Settings, output: OutputFormat) -> Result<(), String> {
+    let (remote_templates, errors) = lookup::remote_templates(settings);
+    for e in errors {
+        print_error(output, &e);
+    }
+    // cache remote templates for shell completion
+    let cache_file = completion::remote_templates_cache();
+    if let Ok(json_text) = serde_json::to_string(&remote_templates) {
+        fs::create_dir_all(paths::cache_dir())
+            .and_then(|_| fs::write(cache_file, json_text))
+            .unwrap_or_default();
+    }
+    if !output.is_table() {
+        output.print(&remote_templates);
+        return Ok(());
+    }
+    for (i, remote_template) in (1..).zip(remote_templates.iter()) {
+        let (name, url, description) = match remote_template {
+            lookup::RemoteTemplate::Github(repo) => (&repo.name, &repo.html_url, &repo.description),
+            lookup::RemoteTemplate::Registry(entry) => {
+                (&entry.name, &entry.repository, &entry.description)
+            }
+        };
+        println!(
+            "{}. {} - {} : {}",
+            i,
+            name.as_str().blue(),
+            url,
+            description
+        );
+    }
+    Ok(())
+}
+
+/// print error in red for table output, and to stderr for json/yaml output
+fn print_error(output: OutputFormat, message: &str) {
+    if output.is_table() {
+        println!("{}", message.red());
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+fn lint_template(template_dir: &Path) -> Result<(), String> {
+    let issues = lint::lint_template(template_dir);
+    if issues.is_empty() {
+        println!("{}", "💯 No problem found in template!".green());
+        return Ok(());
+    }
+    for issue in issues.iter() {
+        println!("{}", issue.to_string().as_str().red());
+    }
+    Err(format!("{} problems found in template!", issues.len()))
+}
+
+fn test_template(template_dir: &Path, update: bool) -> Result<(), String> {
+    let template_file = format::find_file(template_dir, format::TEMPLATE_FILE_NAMES).ok_or(
+        format!("Template file not found in {}", template_dir.display()),
+    )?;
+    let app_template = AppTemplate::from_file(&template_file)?;
+    let cases = harness::load_cases(template_dir)?;
+    if cases.is_empty() {
+        return Err(format!(
+            "No test case found, please add {}/<case>.json",
+            harness::TESTS_DIR
+        ));
+    }
+    let mut failed = 0;
+    for (name, case) in cases.iter() {
+        let report = harness::run_case(template_dir, &app_template, name, case, update);
+        if report.passed() {
+            let status = if update { "updated" } else { "passed" };
+            println!("✅ {} {}", report.name, status);
+        } else {
+            failed += 1;
+            println!("{}", format!("❌ {} failed", report.name).as_str().red());
+            for failure in report.failures.iter() {
+                println!("   {}", failure);
+            }
+        }
+    }
+    println!("{} passed, {} failed", cases.len() - failed, failed);
+    if failed > 0 {
+        return Err(format!("{} test cases failed", failed));
+    }
+    Ok(())
+}
+
+fn init_template(project_dir: &Path, vars: &[String], yes: bool) -> Result<(), String> {
+    let replacements = vars
+        .iter()
+        .map(|var| scaffold::Replacement::parse(var))
+        .collect::<Result<Vec<_>, String>>()?;
+    let plan = scaffold::plan(project_dir, &replacements)?;
+    if plan.files.is_empty() && plan.renames.is_empty() {
+        return Err("No file or path contains the values!".to_string());
+    }
+    println!("files to rewrite:");
+    for (file, count) in plan.files.iter() {
+        println!("  {} ({} occurrences)", file, count);
+    }
+    if !plan.renames.is_empty() {
+        println!("paths to rename:");
+        for (from, to) in plan.renames.iter() {
+            println!("  {} -> {}", from, to.as_str().green());
+        }
+    }
+    if !yes {
+        print!(
+            "Rewrite project and generate template.json? [y/N]{}",
+            ">".blue()
+        );
+        let input = read_line()?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            return Ok(());
+        }
+    }
+    scaffold::apply(project_dir, &plan, &replacements)?;
+    println!(
+        "🎉 template.json generated, please run 'tgm lint {}' to check",
+        project_dir.display()
+    );
+    Ok(())
 }
 
-fn list_remote_templates(settings: &Settings) {
-    let org_name = get_central(settings);
-    if let Ok(repos) = GithubRepo::fetch_tgm_template_repos(&org_name) {
-        let mut i = 1;
-        for repo in repos {
-            // ignore repo name starts with ".", such as '.github'
-            if !repo.name.starts_with(".") {
+fn manage_registry(command: Option<RegistryCommand>) -> Result<(), String> {
+    match command {
+        Some(RegistryCommand::Build { dir, dest }) => {
+            let index = RegistryIndex::build(&dir)
+                .map_err(|e| format!("Failed to build registry index: {}", e))?;
+            if let Some(dest) = dest {
+                let text = index.to_text(registry::is_toml(&dest));
+                fs::write(&dest, text).map_err(|e| format!("Failed to write {}: {}", dest, e))?;
                 println!(
-                    "{}. {} - {} : {}",
-                    i,
-                    repo.name.as_str().blue(),
-                    repo.html_url,
-                    repo.description
+                    "📦 Registry index {} created with {} templates!",
+                    dest,
+                    index.templates.len()
                 );
-                i = i + 1;
+            } else {
+                println!("{}", index.to_text(false));
             }
         }
-    } else {
-        println!("Failed to fetch remote templates");
+        Some(RegistryCommand::Check { source }) => {
+            let index = RegistryIndex::load(&source)?;
+            licenses::registry_licenses(&source)?;
+            println!(
+                "{}",
+                format!(
+                    "💯 {} is valid with {} templates!",
+                    source,
+                    index.templates.len()
+                )
+                .green()
+            );
+        }
+        Some(RegistryCommand::Add { source }) => {
+            RegistryIndex::load(&source)?;
+            let mut settings = Settings::load_user()?;
+            if settings.add_registry(&source)? {
+                println!("{} registry added!", source);
+            } else {
+                println!("{} registry already exists!", source);
+            }
+        }
+        Some(RegistryCommand::Remove { source }) => {
+            let mut settings = Settings::load_user()?;
+            if settings.remove_registry(&source)? {
+                println!("{} registry removed!", source);
+            } else {
+                println!("{} registry not found!", source);
+            }
+        }
+        None => {
+            for source in Settings::load()?.registries.iter().flatten() {
+                println!("{}", source);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// templates from local settings, and from registries and GitHub if remote, and errors are printed in red
+fn collect_candidates(settings: &Settings, remote: bool) -> Vec<Candidate> {
+    let (candidates, errors) = lookup::candidates(settings, remote);
+    for e in errors {
+        println!("{}", e.as_str().red());
+    }
+    candidates
+}
+
+fn search_templates(
+    settings: &Settings,
+    query: &str,
+    tags: &[String],
+    remote: bool,
+    output: OutputFormat,
+) -> Result<(), String> {
+    let candidates = collect_candidates(settings, remote);
+    let hits = search::search(&candidates, query, tags);
+    if !output.is_table() {
+        let matched: Vec<&Candidate> = hits.iter().map(|hit| hit.candidate).collect();
+        output.print(&matched);
+        return Ok(());
+    }
+    if hits.is_empty() {
+        println!("😂 No template matched!");
+        return Ok(());
     }
+    for (i, hit) in (1..).zip(hits.iter()) {
+        let template = &hit.candidate.template;
+        let mut line = format!(
+            "{}. {} [{}] - {} : {}",
+            i,
+            template.name.as_str().blue(),
+            hit.candidate.source,
+            template.repository,
+            template.description
+        );
+        if !template.tags.is_empty() {
+            line.push_str(&format!(" ({})", template.tags.join(", ").as_str().green()));
+        }
+        println!("{}", line);
+    }
+    Ok(())
 }
 
-fn show_global_variables(settings: &Settings) {
+/// variables injected by tgm
+fn show_global_variables(settings: &Settings, output: OutputFormat) -> Result<(), String> {
+    let date_format = settings.find_variable_value("date_format");
+    let injected = builtins::builtin_variables(Path::new("."), date_format.as_deref());
+    if !output.is_table() {
+        #[derive(serde::Serialize)]
+        struct GlobalVariables<'a> {
+            variables: &'a Vec<Variable>,
+            injected: &'a Vec<Variable>,
+        }
+        let variables: Vec<Variable> = settings.variables.iter().map(Variable::masked).collect();
+        output.print(&GlobalVariables {
+            variables: &variables,
+            injected: &injected,
+        });
+        return Ok(());
+    }
     println!("======Global customized variables=========");
-    for variable in settings.variables.iter() {
-        if variable.value.is_some() {
-            println!("{}: {}", &variable.name, variable.value.clone().unwrap());
+    for variable in settings.variables.iter().map(Variable::masked) {
+        if let Some(value) = &variable.value {
+            println!("{}: {}", &variable.name, value);
         }
     }
     println!("======Injected variables==============");
-    let now: DateTime<Local> = Local::now();
-    println!("current_year: {}", now.year().to_string());
-    println!("current_date: {}", now.format("%m/%d/%Y").to_string());
-    println!("os_name: {}", String::from(env::consts::OS));
-    println!("os_family: {}", String::from(env::consts::FAMILY));
-    println!("os_arch: {}", String::from(env::consts::ARCH));
+    for variable in injected.iter() {
+        println!("{}: {}", variable.name, variable.value.clone().unwrap());
+    }
+    Ok(())
+}
+
+fn manage_config(
+    settings: &Settings,
+    command: ConfigCommand,
+    output: OutputFormat,
+) -> Result<(), String> {
+    match command {
+        ConfigCommand::Set {
+            name,
+            value,
+            desc,
+            secret,
+        } => {
+            let mut user_settings = Settings::load_user()?;
+            user_settings.set_value(&name, &value, desc.as_deref());
+            let mut display_value = value.clone();
+            if let Some(variable) = user_settings.variables.iter_mut().find(|v| v.name == name) {
+                variable.secret = variable.secret || secret;
+                display_value = variable.display_value(&value);
+                if variable.secret && !secret::is_reference(&value) {
+                    let hint = "⚠️ Secret is saved in plaintext, and env:NAME, file:path or cmd:command reference is recommended";
+                    println!("{}", hint.yellow());
+                }
+            }
+            user_settings.flush()?;
+            println!("{} = {}", name.green(), display_value);
+        }
+        ConfigCommand::Get { name, reveal } => {
+            let value = if reveal {
+                settings.get_value(&name)
+            } else {
+                settings.get_display_value(&name)
+            };
+            let value = value.ok_or(format!("{} not found", name))?;
+            println!("{}", value);
+        }
+        ConfigCommand::Unset { name } => {
+            let mut user_settings = Settings::load_user()?;
+            if !user_settings.unset_value(&name) {
+                return Err(format!(
+                    "{} not found in {}",
+                    name,
+                    user_settings.file_path.display()
+                ));
+            }
+            user_settings.flush()?;
+            println!("{} removed!", name);
+        }
+        ConfigCommand::List => {
+            let mut values: Vec<(String, String)> = vec![];
+            for key in [
+                "central",
+                "github_api",
+                "github_token",
+                "registries",
+                "trusted_projects",
+            ] {
+                if let Some(value) = settings.get_display_value(key) {
+                    values.push((key.to_string(), value));
+                }
+            }
+            for variable in settings.variables.iter() {
+                let value = variable.masked().value.unwrap_or_default();
+                values.push((variable.name.clone(), value));
+            }
+            if !output.is_table() {
+                let values: std::collections::BTreeMap<String, String> =
+                    values.into_iter().collect();
+                output.print(&values);
+                return Ok(());
+            }
+            for (key, value) in values.iter() {
+                println!("{} = {}", key.as_str().green(), value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// show settings values with the settings file where each value came from
+fn show_settings_origin(settings: &Settings, output: OutputFormat) -> Result<(), String> {
+    let mut values: Vec<(String, String)> = vec![];
+    if let Some(central) = &settings.central {
+        values.push(("central".to_string(), central.clone()));
+    }
+    if let Some(github_api) = &settings.github_api {
+        values.push(("github_api".to_string(), github_api.clone()));
+    }
+    if settings.github_token.is_some() {
+        values.push(("github_token".to_string(), "******".to_string()));
+    }
+    for source in settings.registries.iter().flatten() {
+        values.push((format!("registry.{}", source), source.clone()));
+    }
+    for template in settings.templates.iter() {
+        let key = format!("template.{}", template.name);
+        values.push((key, template.repository.clone()));
+    }
+    for variable in settings.variables.iter() {
+        let key = format!("variable.{}", variable.name);
+        values.push((key, variable.masked().value.unwrap_or_default()));
+    }
+    let origin = |key: &str| {
+        settings
+            .origin(key)
+            .map(|path| path.display().to_string())
+            .unwrap_or_default()
+    };
+    if !output.is_table() {
+        #[derive(serde::Serialize)]
+        struct OriginValue {
+            key: String,
+            value: String,
+            origin: String,
+        }
+        let origin_values: Vec<OriginValue> = values
+            .into_iter()
+            .map(|(key, value)| OriginValue {
+                origin: origin(&key),
+                key,
+                value,
+            })
+            .collect();
+        output.print(&origin_values);
+        return Ok(());
+    }
+    for (key, value) in values.iter() {
+        println!("{}\t{}: {}", origin(key).as_str().blue(), key, value);
+    }
+    Ok(())
+}
+
+fn list_licenses(registries: &[String]) -> Result<(), String> {
+    let (licenses, errors) = licenses::available_licenses(registries);
+    for e in errors {
+        println!("{}", e.as_str().red());
+    }
+    for license in licenses.iter() {
+        let source = license.source.as_deref().unwrap_or("built-in");
+        println!(
+            "{:<20}{:<50}{}",
+            license.id.green(),
+            license.name,
+            source.blue()
+        );
+    }
+    Ok(())
+}
+
+/// write file, and existing file is kept unless `force` is true
+fn write_new_file(path: &Path, text: &str, force: bool) -> Result<(), String> {
+    licenses::write_new_file(path, text, force)?;
+    println!("📄 {} file created", path.display());
+    Ok(())
+}
+
+fn update_license_headers(
+    dir: &Path,
+    license_id: &str,
+    registries: &[String],
+    author_name: &str,
+    project_name: &str,
+    full: bool,
+    check: bool,
+) -> Result<(), String> {
+    let license = licenses::resolve_license(license_id, registries)?;
+    let year = chrono::Local::now().format("%Y").to_string();
+    let changes =
+        headers::license_headers(dir, &license, &year, author_name, project_name, full, check)
+            .map_err(|e| format!("Failed to update license headers: {}", e))?;
+    for (path, status) in changes.iter() {
+        let status = match (status, check) {
+            (HeaderStatus::Inserted, true) => "missing",
+            (_, true) => "outdated",
+            (HeaderStatus::Inserted, false) => "inserted",
+            _ => "updated",
+        };
+        println!("{}: {}", path.display(), status);
+    }
+    if check && !changes.is_empty() {
+        return Err(format!(
+            "{} files without up-to-date license header",
+            changes.len()
+        ));
+    }
+    println!("✅ License headers are up to date");
+    Ok(())
+}
+
+fn current_dir_name() -> String {
+    env::current_dir()
+        .ok()
+        .and_then(|dir| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_default()
 }
 
-fn config_global_variables() {
-    let variable_names = vec![
+fn config_global_variables() -> Result<(), String> {
+    let variable_names = [
         ("author_name", "author's name"),
         ("author_email", "author's email"),
         ("github_user_name", "author's Github user name"),
         ("open_source_license", "Open Source License"),
     ];
-    let mut settings = Settings::load();
+    let mut settings = Settings::load_user()?;
     for pair in variable_names.iter() {
-        let global_variable = settings.find_variable_value(&pair.0);
+        let global_variable = settings.find_variable_value(pair.0);
         if let Some(variable_value) = global_variable.clone() {
             print!(
                 "Define value for variable '{}'({}): {} : {}",
@@ -269,159 +771,215 @@ fn config_global_variables() {
                 ">".blue()
             );
         }
-        std::io::stdout().flush().unwrap();
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input).unwrap();
+        let mut input = read_line()?;
         if input.trim().is_empty() {
             if let Some(variable_value) = global_variable.clone() {
                 input = variable_value.clone();
             }
         }
-        settings.set_variable(pair.0, &input.trim(), pair.1);
+        settings.set_variable(pair.0, input.trim(), pair.1);
     }
-    settings.flush();
+    settings.flush()
 }
 
-fn get_central(settings: &Settings) -> String {
-    let mut org_name = String::from("tgm-templates");
-    if settings.central.is_some() {
-        org_name = settings.central.clone().unwrap();
-    }
-    org_name
+fn convert_settings(format: format::FileFormat) -> Result<(), String> {
+    let mut settings = Settings::load_user()?;
+    let file_path = settings.convert(format)?;
+    println!("🎉 Settings converted to {}", file_path.display());
+    Ok(())
 }
 
-fn create_app(template_name: &str, workspace_dir: &str, app_dir: &str, settings: &Settings) {
-    let dest_dir = format!("{}/{}", workspace_dir, app_dir);
-    let mut repo_url: String = String::new();
-    if let Some(template) = settings.find_template(&template_name) {
-        repo_url = template.repository.clone();
-    } else {
-        // load template from https://github.com/tgm-templates/
-        let org_name = get_central(settings);
-        if let Ok(repos) = GithubRepo::fetch_tgm_template_repos(&org_name) {
-            for repo in repos {
-                if repo.name == template_name {
-                    repo_url = repo.html_url.clone();
-                    break;
-                }
-            }
+fn show_template_info(
+    settings: &Settings,
+    name: &str,
+    refresh: bool,
+    output: OutputFormat,
+) -> Result<(), String> {
+    let (mut app_template, template) = lookup::load_app_template(settings, name, refresh)?;
+    let git_ref = template.git_ref;
+    if let Some(variables) = app_template.variables.as_mut() {
+        for variable in variables.iter_mut() {
+            *variable = variable.masked();
         }
     }
-    println!("repo: {}", repo_url);
-    if !repo_url.is_empty() {
-        println!("🚴 Beginning to create app from {}", template_name);
-        let args = vec!["clone", "--depth", "1", repo_url.as_str(), app_dir];
-        match execute_command("git", &args) {
-            Ok(stdout_text) => {
-                println!("{}", stdout_text);
+    if !output.is_table() {
+        #[derive(serde::Serialize)]
+        struct TemplateInfo<'a> {
+            #[serde(flatten)]
+            app_template: &'a AppTemplate,
+            #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+            git_ref: Option<String>,
+        }
+        let info = TemplateInfo {
+            app_template: &app_template,
+            git_ref,
+        };
+        output.print(&info);
+        return Ok(());
+    }
+    println!(
+        "{} - {}",
+        app_template.name.as_str().blue(),
+        app_template.description
+    );
+    println!("repository: {}", app_template.repository);
+    if let Some(git_ref) = git_ref {
+        println!("ref: {}", git_ref);
+    }
+    if let Some(license) = &app_template.license {
+        println!("license: {}", license);
+    }
+    if let Some(tags) = &app_template.tags {
+        println!("tags: {}", tags.join(", "));
+    }
+    if let Some(variables) = &app_template.variables {
+        println!("variables:");
+        for variable in variables.iter() {
+            let mut line = format!(
+                "  {} ({}): {}",
+                variable.name.as_str().green(),
+                variable.type_name(),
+                variable.description
+            );
+            if let Some(value) = &variable.value {
+                line.push_str(&format!(", default: {}", value));
+            }
+            if let Some(pattern) = &variable.pattern {
+                line.push_str(&format!(", pattern: {}", pattern));
             }
-            Err(e) => {
-                println!("{}", e.as_str().red());
+            if let Some(choices) = &variable.choices {
+                line.push_str(&format!(", choices: {}", choices.join(" | ")));
             }
+            println!("{}", line);
         }
-        // template variables input
-        prompt_input_variables(&settings, &dest_dir);
-    } else {
-        println!(
-            "{}",
-            format!("😂 Template not found: {}", template_name)
-                .as_str()
-                .red()
-        );
     }
-}
-
-fn execute_command(command: &str, args: &[&str]) -> Result<String, String> {
-    let result = Command::new(command).args(args).output();
-    match result {
-        Ok(output) => std::str::from_utf8(output.stdout.as_slice())
-            .map(String::from)
-            .map_err(|e| e.to_string()),
-        Err(e) => Err(e.to_string()),
+    if let Some(files) = &app_template.files {
+        println!("files:");
+        for file in files.iter() {
+            println!("  {}", file);
+        }
     }
+    if let Some(post_create) = &app_template.post_create {
+        println!("hooks:");
+        println!("  post_create: {}", post_create);
+    }
+    Ok(())
 }
 
-fn prompt_input_variables(settings: &Settings, app_dest_dir: &str) {
-    let template_json_file = format!("{}/template.json", app_dest_dir);
-    let app_template = AppTemplate::new(&template_json_file);
-    let mut variables = HashMap::<String, String>::new();
-    //default global variables
-    let now: DateTime<Local> = Local::now();
-    variables.insert(String::from("current_year"), now.year().to_string());
-    variables.insert(
-        String::from("current_date"),
-        now.format("%m/%d/%Y").to_string(),
-    );
-    //os related variables
-    variables.insert(String::from("os_name"), String::from(env::consts::OS));
-    variables.insert(
-        String::from("os_family"),
-        String::from(env::consts::FAMILY),
-    );
-    variables.insert(
-        String::from("os_arch"),
-        String::from(env::consts::ARCH),
-    );
-    if app_template.variables.is_some() {
-        println!("🤗 Please complete template variables.");
-        for v in app_template.variables.unwrap().iter() {
-            let mut value = prompt_input_variable(settings, v);
-            // regex pattern match - only once
-            if v.pattern.is_some() {
-                let pattern = v.pattern.clone().unwrap();
-                if let Ok(regex) = Regex::new(&pattern) {
-                    if !regex.is_match(&value) {
-                        let hint = format!(
-                            "😅 '{}' is illegal, and should match with '{}' regex pattern!",
-                            value, pattern
-                        );
-                        println!("{}", hint.as_str().red());
-                        value = prompt_input_variable(settings, v);
-                    }
-                }
-            }
-            variables.insert(format!("@{}@", v.name), String::from(value));
-        }
-        if app_template.files.is_some() {
-            for file in app_template.files.unwrap().iter() {
-                let resource_file = format!("{}/{}", app_dest_dir, file);
-                replace_variables(&resource_file, &variables);
-            }
+/// create app with generator, and template variables are prompted after template fetched
+fn create_app(template_name: &str, app_dir: &Path, settings: &Settings) -> Result<(), String> {
+    let generator = Generator::new(settings, template_name, app_dir)?;
+    println!("repo: {}", generator.template().repository);
+    println!("🚴 Beginning to create app from {}", template_name);
+    let app_template = generator.fetch()?;
+    // fetched template is removed if input failed, so app could be created again
+    let answers = prompt_input_variables(settings, &app_template)
+        .inspect_err(|_| fs::remove_dir_all(app_dir).unwrap_or_default())?;
+    let report = generator.render(&app_template, &answers)?;
+    for warning in &report.warnings {
+        println!("{}", format!("😂 {}", warning).red());
+    }
+    for (file_name, license_id) in &report.license_files {
+        println!(
+            "📄 {} file created with {}",
+            file_name.display(),
+            license_id
+        );
+    }
+    if let Some(license_id) = &report.license {
+        for file_name in &report.manifests {
+            println!(
+                "📄 License of {} updated to {}",
+                file_name.display(),
+                license_id
+            );
         }
     }
-    env::set_current_dir(Path::new(app_dest_dir)).unwrap();
-    // re-init
-    execute_command("rm", &["-rf", ".git"]).unwrap_or(String::new());
-    execute_command("git", &["init"]).unwrap_or(String::new());
     // post create
-    if let Some(post_create) = app_template.post_create {
-        if !post_create.is_empty() {
-            let parts: Vec<&str> = post_create.split(' ').collect();
-            println!("🏃 Begin to execute post_create: {}", post_create);
-            let mut args: Vec<&str> = vec![];
-            if parts.len() > 1 {
-                args = parts[1..].to_vec();
+    if let Some(post_create) = &report.post_create {
+        let parts: Vec<&str> = post_create.split(' ').collect();
+        println!("🏃 Begin to execute post_create: {}", post_create);
+        Command::new(parts[0])
+            .args(&parts[1..])
+            .current_dir(&report.destination)
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|e| format!("Failed to execute post_create: {}", e))?;
+    }
+    Ok(())
+}
+
+/// answers of template variables from terminal
+fn prompt_input_variables(
+    settings: &Settings,
+    app_template: &AppTemplate,
+) -> Result<HashMap<String, String>, String> {
+    let mut answers = HashMap::new();
+    if let Some(template_variables) = &app_template.variables {
+        println!("🤗 Please complete template variables.");
+        for v in template_variables.iter() {
+            let mut value = prompt_input_variable(settings, v)?;
+            // regex pattern, choices and value type check - only once
+            if let Err(reason) = v.validate(&value) {
+                let hint = format!(
+                    "😅 '{}' is illegal, and {}!",
+                    v.display_value(&value),
+                    reason
+                );
+                println!("{}", hint.as_str().red());
+                value = prompt_input_variable(settings, v)?;
             }
-            let _ = Command::new(parts[0])
-                .args(&args)
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .output()
-                .unwrap();
+            answers.insert(v.name.clone(), value);
         }
     }
-    // delete template.json
-    execute_command("rm", &["-rf", "template.json"]).unwrap_or(String::new());
+    Ok(answers)
 }
 
-fn prompt_input_variable(settings: &Settings, v: &Variable) -> String {
-    let global_variable = settings.find_variable_value(&v.name);
+fn prompt_input_variable(settings: &Settings, v: &Variable) -> Result<String, String> {
+    // references in secret global variables, such as env:NPM_TOKEN, are resolved
+    let global_variable = match settings.resolve_variable_value(&v.name) {
+        Ok(value) => value,
+        Err(e) => {
+            let hint = format!("😂 Failed to resolve global variable '{}': {}", v.name, e);
+            println!("{}", hint.as_str().red());
+            None
+        }
+    };
+    let is_secret = v.secret
+        || settings
+            .variables
+            .iter()
+            .any(|variable| variable.name == v.name && variable.secret);
+    if is_secret {
+        if let Some(variable_value) = global_variable {
+            println!(
+                "🔑 Use value of secret variable '{}' from global variables",
+                v.name.as_str().green()
+            );
+            return Ok(variable_value);
+        }
+        if prompt::is_interactive() {
+            return Ok(prompt_form_input(v, "", true));
+        }
+        let prompt = format!(
+            "🔑 Define value for secret variable '{}'({}){}",
+            v.name.as_str().green(),
+            v.description,
+            ">".blue()
+        );
+        let input = rpassword::prompt_password(prompt).unwrap_or_default();
+        return Ok(String::from(input.trim()));
+    }
     let mut default_value = String::new();
     if global_variable.is_some() {
         default_value = global_variable.clone().unwrap();
     } else if v.value.is_some() {
         default_value = v.value.clone().unwrap();
     }
+    if prompt::is_interactive() {
+        return Ok(prompt_form_input(v, &default_value, false));
+    }
     if !default_value.is_empty() {
         print!(
             "👉 Define value for variable '{}'({}): {} : {}",
@@ -438,25 +996,35 @@ fn prompt_input_variable(settings: &Settings, v: &Variable) -> String {
             ">".blue()
         );
     }
-    std::io::stdout().flush().unwrap();
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
+    let mut input = read_line()?;
     if input.trim().is_empty() {
-        if let Some(variable_value) = global_variable.clone() {
-            input = variable_value.clone();
-        }
+        input = default_value;
     }
-    String::from(input.trim())
+    Ok(String::from(input.trim()))
+}
+
+/// read a line from stdin after prompt printed
+fn read_line() -> Result<String, String> {
+    std::io::stdout()
+        .flush()
+        .map_err(|e| format!("Failed to write prompt: {}", e))?;
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Failed to read input: {}", e))?;
+    Ok(input)
 }
 
-fn replace_variables(resource_file: &str, variables: &HashMap<String, String>) {
-    let path = Path::new(resource_file);
-    let mut replaced_text = fs::read_to_string(path).unwrap();
-    for (k, v) in variables.iter() {
-        replaced_text = replaced_text.replacen(k.as_str(), v.as_str(), 1024);
+/// form-style input on terminal, and tgm exits if input is interrupted
+fn prompt_form_input(v: &Variable, default_value: &str, secret: bool) -> String {
+    match prompt::input_variable(v, default_value, secret) {
+        Ok(value) => value,
+        Err(e) => {
+            let hint = format!("😂 Failed to input variable '{}': {}", v.name, e);
+            println!("{}", hint.as_str().red());
+            std::process::exit(1);
+        }
     }
-    let mut file = File::create(path).unwrap();
-    file.write_all(replaced_text.as_bytes()).unwrap();
 }
 
 #[cfg(test)]
@@ -465,30 +1033,38 @@ mod tests {
 
     #[test]
     fn test_list_templates() {
-        let settings = Settings::load();
-        list_templates(&settings);
+        paths::use_test_home();
+        let settings = Settings::load().unwrap();
+        list_templates(&settings, OutputFormat::Table).unwrap();
     }
 
     #[test]
     fn test_create_app() {
-        let settings = Settings::load();
+        paths::use_test_home();
+        let settings = Settings::load().unwrap();
         let template_name = "spring-boot-java";
-        let app_dir = "temp/demo";
-        let current_dir = String::from(env::current_dir().unwrap().to_str().unwrap());
-        create_app(template_name, &current_dir, app_dir, &settings);
+        let app_dir = env::current_dir().unwrap().join("temp/demo");
+        if let Err(e) = create_app(template_name, &app_dir, &settings) {
+            println!("{}", e);
+        }
     }
 
     #[test]
     fn test_add_template() {
-        let name = "demo";
-        let url = "git://xxx";
-        let description = "no description";
-        add_template(name, url, description);
+        paths::use_test_home();
+        add_template(Template {
+            name: String::from("demo"),
+            repository: String::from("git://xxx"),
+            description: String::from("no description"),
+            ..Default::default()
+        })
+        .unwrap();
     }
 
     #[test]
     fn test_delete_template() {
+        paths::use_test_home();
         let name = "demo";
-        delete_template(name);
+        delete_template(name).unwrap();
     }
 }
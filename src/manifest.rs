@@ -0,0 +1,209 @@
+//! license field of package manifests: Cargo.toml, package.json, pom.xml and pyproject.toml
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+/// manifest file names with license field
+pub const MANIFEST_FILE_NAMES: &[&str] =
+    &["Cargo.toml", "package.json", "pom.xml", "pyproject.toml"];
+
+/// update license field of manifests in the directory, and updated file names are returned.
+/// Cargo.toml, package.json and pyproject.toml get the whole SPDX expression,
+/// and pom.xml gets one `<license>` per identifier of `license_ids`.
+pub fn update_license(
+    dir: &Path,
+    expression: &str,
+    license_ids: &[&str],
+) -> Result<Vec<String>, String> {
+    let mut updated_files = vec![];
+    for file_name in MANIFEST_FILE_NAMES {
+        let path = dir.join(file_name);
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let new_text = match *file_name {
+            "Cargo.toml" => set_toml_value(&text, &["package"], "license", expression),
+            "package.json" => set_json_license(&text, expression),
+            "pom.xml" => set_pom_license(&text, license_ids),
+            _ => set_toml_value(&text, &["project", "tool.poetry"], "license", expression),
+        };
+        if let Some(new_text) = new_text.filter(|new_text| *new_text != text) {
+            fs::write(&path, new_text)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            updated_files.push(file_name.to_string());
+        }
+    }
+    Ok(updated_files)
+}
+
+/// set string value of key in the first present table, and None if no table present.
+/// Text is edited line by line to keep comments and layout.
+fn set_toml_value(text: &str, tables: &[&str], key: &str, value: &str) -> Option<String> {
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let key_line = format!("{} = \"{}\"", key, value);
+    let key_regex = Regex::new(&format!(r"^\s*{}\s*=", regex::escape(key))).unwrap();
+    let start = tables.iter().find_map(|table| {
+        let header = format!("[{}]", table);
+        lines.iter().position(|line| line.trim() == header)
+    })? + 1;
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |index| start + index);
+    if let Some(index) = (start..end).find(|index| key_regex.is_match(&lines[*index])) {
+        lines[index] = key_line;
+    } else {
+        // after the last key of the table
+        let index = (start..end)
+            .rev()
+            .find(|index| !lines[*index].trim().is_empty())
+            .map_or(start, |index| index + 1);
+        lines.insert(index, key_line);
+    }
+    Some(join_lines(text, &lines))
+}
+
+fn set_json_license(text: &str, license_id: &str) -> Option<String> {
+    let license_regex = Regex::new(r#"("license"\s*:\s*)"[^"]*""#).unwrap();
+    if license_regex.is_match(text) {
+        let replacement = format!("${{1}}\"{}\"", license_id);
+        return Some(
+            license_regex
+                .replace(text, replacement.as_str())
+                .to_string(),
+        );
+    }
+    // after "version", or the first line of object
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let index = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("\"version\""))
+        .or_else(|| lines.iter().position(|line| line.trim() == "{"))?;
+    let indent = match lines.get(index + 1) {
+        Some(line) if lines[index].trim() == "{" => {
+            line[..line.len() - line.trim_start().len()].to_string()
+        }
+        _ => lines[index][..lines[index].len() - lines[index].trim_start().len()].to_string(),
+    };
+    let has_next = lines
+        .get(index + 1)
+        .is_some_and(|line| line.trim_start().starts_with('"'));
+    if lines[index].trim() != "{" && !lines[index].trim_end().ends_with(',') {
+        lines[index].push(',');
+    }
+    let comma = if has_next { "," } else { "" };
+    lines.insert(
+        index + 1,
+        format!("{}\"license\": \"{}\"{}", indent, license_id, comma),
+    );
+    Some(join_lines(text, &lines))
+}
+
+fn set_pom_license(text: &str, license_ids: &[&str]) -> Option<String> {
+    let licenses_regex = Regex::new(r"(?s)([ \t]*)<licenses>.*?</licenses>").unwrap();
+    let licenses = |indent: &str| {
+        let mut lines = vec![format!("{}<licenses>", indent)];
+        for license_id in license_ids {
+            lines.push(format!(
+                "{0}    <license>\n{0}        <name>{1}</name>\n{0}        <url>https://spdx.org/licenses/{1}.html</url>\n{0}    </license>",
+                indent, license_id
+            ));
+        }
+        lines.push(format!("{}</licenses>", indent));
+        lines.join("\n")
+    };
+    if let Some(captures) = licenses_regex.captures(text) {
+        let range = captures.get(0).unwrap().range();
+        return Some(format!(
+            "{}{}{}",
+            &text[..range.start],
+            licenses(&captures[1]),
+            &text[range.end..]
+        ));
+    }
+    // before properties, dependencies or build, otherwise at the end of project
+    let anchor_regex =
+        Regex::new(r"(?m)^([ \t]*)(<properties|<dependencies|<build|</project)[\s/>]").unwrap();
+    let captures = anchor_regex.captures(text)?;
+    let start = captures.get(0).unwrap().start();
+    let indent = if &captures[2] == "</project" {
+        format!("{}    ", &captures[1])
+    } else {
+        captures[1].to_string()
+    };
+    Some(format!(
+        "{}{}\n{}",
+        &text[..start],
+        licenses(&indent),
+        &text[start..]
+    ))
+}
+
+fn join_lines(text: &str, lines: &[String]) -> String {
+    let mut new_text = lines.join("\n");
+    if text.ends_with('\n') {
+        new_text.push('\n');
+    }
+    new_text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_license() {
+        let cargo_toml = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[dependencies]\n";
+        assert_eq!(
+            set_toml_value(cargo_toml, &["package"], "license", "MIT").unwrap(),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nlicense = \"MIT\"\n\n[dependencies]\n"
+        );
+        let pyproject = "[tool.poetry]\nname = \"demo\"\nlicense = \"GPL\"\n";
+        assert_eq!(
+            set_toml_value(pyproject, &["project", "tool.poetry"], "license", "MIT").unwrap(),
+            "[tool.poetry]\nname = \"demo\"\nlicense = \"MIT\"\n"
+        );
+        let package_json =
+            "{\n  \"name\": \"demo\",\n  \"version\": \"1.0.0\",\n  \"main\": \"index.js\"\n}\n";
+        assert_eq!(
+            set_json_license(package_json, "MIT").unwrap(),
+            "{\n  \"name\": \"demo\",\n  \"version\": \"1.0.0\",\n  \"license\": \"MIT\",\n  \"main\": \"index.js\"\n}\n"
+        );
+        let package_json = "{\"name\": \"demo\", \"license\": \"ISC\"}";
+        assert_eq!(
+            set_json_license(package_json, "MIT").unwrap(),
+            "{\"name\": \"demo\", \"license\": \"MIT\"}"
+        );
+        let pom_xml =
+            "<project>\n    <artifactId>demo</artifactId>\n    <dependencies/>\n</project>\n";
+        let pom_xml = set_pom_license(pom_xml, &["Apache-2.0"]).unwrap();
+        assert!(pom_xml
+            .contains("    <licenses>\n        <license>\n            <name>Apache-2.0</name>\n"));
+        assert!(pom_xml.ends_with("    </licenses>\n    <dependencies/>\n</project>\n"));
+        let pom_xml = set_pom_license(&pom_xml, &["MIT"]).unwrap();
+        assert!(pom_xml.contains("<name>MIT</name>"));
+        assert_eq!(pom_xml.matches("<licenses>").count(), 1);
+    }
+
+    #[test]
+    fn test_update_license_expression() {
+        let dir = std::env::temp_dir().join(format!("tgm-manifest-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        fs::write(
+            dir.join("pom.xml"),
+            "<project>\n    <dependencies/>\n</project>\n",
+        )
+        .unwrap();
+        let file_names = update_license(&dir, "MIT OR Apache-2.0", &["MIT", "Apache-2.0"]);
+        let cargo_toml = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        let pom_xml = fs::read_to_string(dir.join("pom.xml")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(file_names.unwrap(), vec!["Cargo.toml", "pom.xml"]);
+        assert!(cargo_toml.contains("license = \"MIT OR Apache-2.0\""));
+        assert_eq!(pom_xml.matches("<license>").count(), 2);
+        assert!(pom_xml.contains("<url>https://spdx.org/licenses/MIT.html</url>"));
+        assert!(pom_xml.contains("<url>https://spdx.org/licenses/Apache-2.0.html</url>"));
+        assert!(!pom_xml.contains(" OR "));
+    }
+}
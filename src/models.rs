@@ -1,40 +1,118 @@
 //! models in tgm
-use reqwest::blocking::Client;
-use serde::{Deserialize, Serialize};
-use std::env;
+use crate::format::{find_file, FileFormat, TEMPLATE_FILE_NAMES};
+use crate::github::GithubApi;
+use crate::paths;
+use crate::secret;
+use crate::source::{RepoSource, TemplateSource};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// tgm Settings to include [Template] and [Variable]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Settings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub central: Option<String>,
+    /// registry index sources, local path or http(s) url of index.json/index.toml
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registries: Option<Vec<String>>,
+    /// GitHub API url for GitHub Enterprise, such as https://github.example.com/api/v3
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_api: Option<String>,
+    /// GitHub token, GITHUB_TOKEN or GH_TOKEN environment variable first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    /// project directories whose `cmd:` references in `.tgm/settings.json` are trusted, only from system or user settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted_projects: Option<Vec<String>>,
+    #[serde(default)]
     pub templates: Vec<Template>,
+    #[serde(default)]
     pub variables: Vec<Variable>,
+    /// settings file, and format is decided by extension: json, yaml, yml or toml
+    #[serde(skip)]
+    pub file_path: PathBuf,
+    /// settings file of merged values, such as `central`, `template.{name}` and `variable.{name}`
+    #[serde(skip)]
+    pub origins: BTreeMap<String, PathBuf>,
+    /// project settings file merged into settings
+    #[serde(skip)]
+    pub project_file: Option<PathBuf>,
 }
 
 /// tgm Template
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Template {
     pub name: String,
     pub repository: String,
+    /// git branch or tag to clone
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// sub directory of template in repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
     pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 /// template Variable
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Variable {
     pub name: String,
+    /// value type: string(default), number or boolean
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub var_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern: Option<String>,
+    /// secret value is prompted without echo and masked in output
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub secret: bool,
+    /// allowed values, and value is picked from the list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<String>>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
+/// known fields of template.json
+pub const APP_TEMPLATE_FIELDS: &[&str] = &[
+    "$schema",
+    "name",
+    "repository",
+    "description",
+    "post_create",
+    "schema_version",
+    "tags",
+    "license",
+    "variables",
+    "files",
+];
+
+/// known fields of variable in template.json
+pub const VARIABLE_FIELDS: &[&str] = &[
+    "name",
+    "type",
+    "value",
+    "description",
+    "pattern",
+    "secret",
+    "choices",
+];
+
+/// value types of variable
+pub const VARIABLE_TYPES: &[&str] = &["string", "number", "boolean"];
+
+/// regex pattern of variable name, the same as `@name@` placeholder
+pub const VARIABLE_NAME_PATTERN: &str = "^[A-Za-z_][A-Za-z0-9_]*$";
+
 /// Application template
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AppTemplate {
@@ -43,6 +121,14 @@ pub struct AppTemplate {
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub post_create: Option<String>,
+    /// template.json schema version, 1 if absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// SPDX license id of the template, such as Apache-2.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
     pub variables: Option<Vec<Variable>>,
     pub files: Option<Vec<String>>,
 }
@@ -52,98 +138,312 @@ pub struct AppTemplate {
 pub struct GithubRepo {
     pub name: String,
     pub full_name: String,
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub description: String,
     pub html_url: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+impl Template {
+    /// repository source with template's ref and sub directory
+    pub fn repo_source(&self) -> Option<RepoSource> {
+        match TemplateSource::parse(&self.repository) {
+            Ok(TemplateSource::Repository(mut repo_source)) => {
+                if self.git_ref.is_some() {
+                    repo_source.git_ref = self.git_ref.clone();
+                }
+                if self.subdir.is_some() {
+                    repo_source.subdir = self.subdir.clone();
+                }
+                Some(repo_source)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Variable {
+    pub fn type_name(&self) -> &str {
+        self.var_type.as_deref().unwrap_or("string")
+    }
+
+    /// value for output, and secret value is masked except references, such as `env:NPM_TOKEN`
+    pub fn display_value(&self, value: &str) -> String {
+        if self.secret && !secret::is_reference(value) {
+            secret::MASK.to_string()
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// variable with masked secret value for output
+    pub fn masked(&self) -> Variable {
+        let mut variable = self.clone();
+        variable.value = self.value.as_deref().map(|value| self.display_value(value));
+        variable
+    }
+
+    /// check value with variable type
+    pub fn is_legal_value(&self, value: &str) -> bool {
+        match self.type_name() {
+            "number" => value.parse::<f64>().is_ok(),
+            "boolean" => value == "true" || value == "false",
+            _ => true,
+        }
+    }
+
+    /// check value with choices, and any value is legal without choices
+    pub fn is_choice(&self, value: &str) -> bool {
+        self.choices
+            .as_ref()
+            .is_none_or(|choices| choices.iter().any(|choice| choice == value))
+    }
+
+    /// check value with regex pattern, choices and variable type, and the reason is returned if illegal
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        if let Some(pattern) = &self.pattern {
+            if Regex::new(pattern).is_ok_and(|regex| !regex.is_match(value)) {
+                return Err(format!("should match with '{}' regex pattern", pattern));
+            }
+        }
+        if !self.is_choice(value) {
+            let choices = self.choices.clone().unwrap_or_default();
+            return Err(format!("should be one of {}", choices.join(", ")));
+        }
+        if !self.is_legal_value(value) {
+            return Err(format!("should be {} value", self.type_name()));
+        }
+        Ok(())
+    }
 }
 
 impl GithubRepo {
-    pub fn fetch_tgm_template_repos(org_name: &str) -> reqwest::Result<Vec<GithubRepo>> {
-        let url = format!("https://api.github.com/orgs/{}/repos?type=public", org_name);
-        let response = Client::builder()
-            .build()?
-            .get(&url)
-            .header("Accept", "application/vnd.github.v3+json")
-            .header("User-Agent", "Awesome-tgm-App")
-            .send()?;
-        let mut repos = response.json::<Vec<GithubRepo>>()?;
+    /// fetch all public repositories of the organization
+    pub fn fetch_tgm_template_repos(
+        api: &GithubApi,
+        org_name: &str,
+    ) -> Result<Vec<GithubRepo>, String> {
+        let path = format!("/orgs/{}/repos?type=public&per_page=100", org_name);
+        let mut repos = api.get_pages::<GithubRepo>(&path)?;
         if repos.len() >= 2 {
             repos.sort_by(|a, b| a.name.cmp(&b.name));
         }
         Ok(repos)
     }
+
+    pub fn to_template(&self) -> Template {
+        Template {
+            name: self.name.clone(),
+            repository: self.html_url.clone(),
+            description: self.description.clone(),
+            tags: self.topics.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// values separated by comma, and None if empty
+fn split_values(value: &str) -> Option<Vec<String>> {
+    let values: Vec<String> = value
+        .split(',')
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect();
+    Some(values).filter(|values| !values.is_empty())
+}
+
+/// GitHub returns null for absent description
+fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
 }
 
 impl Settings {
-    pub fn load() -> Settings {
-        let home = env::var("HOME").unwrap();
-        let setting_json_path = format!("{}/.tgm/settings.json", home);
-        let file_path = Path::new(&setting_json_path);
+    /// load merged settings of system, user and project layers, and project layer has the highest precedence.
+    /// Please use [Settings::load_user] to change settings.
+    pub fn load() -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        if let Some(system_file) = paths::system_settings_file() {
+            settings.merge(Settings::load_from(system_file)?);
+        }
+        settings.merge(Settings::load_user()?);
+        if let Some(project_file) = paths::project_settings_file() {
+            let mut project_settings = Settings::load_from(project_file.clone())?;
+            // a project never trusts itself
+            project_settings.trusted_projects = None;
+            settings.merge(project_settings);
+            settings.project_file = Some(project_file);
+        }
+        settings.file_path = paths::settings_file();
+        Ok(settings)
+    }
+
+    /// load user settings from `--config` option, `TGM_HOME`, XDG config directory or ~/.tgm,
+    /// and settings.json, settings.yaml, settings.yml or settings.toml by precedence order
+    pub fn load_user() -> Result<Settings, String> {
+        Settings::load_from(paths::settings_file())
+    }
+
+    /// load settings file, and empty settings if absent
+    pub fn load_from(file_path: PathBuf) -> Result<Settings, String> {
         if file_path.exists() {
-            let setting_json =
-                fs::read_to_string(setting_json_path).expect("Failed to read ~/.tgm/settings.json");
-            let mut settings: Settings = serde_json::from_str(setting_json.as_str()).unwrap();
+            let text = fs::read_to_string(&file_path)
+                .map_err(|e| format!("Failed to read {}: {}", file_path.display(), e))?;
+            let format = FileFormat::from_path(&file_path.to_string_lossy());
+            let mut settings: Settings = format
+                .parse(&text)
+                .map_err(|e| format!("Illegal {}: {}", file_path.display(), e))?;
+            settings.file_path = file_path;
             if settings.templates.len() > 1 {
                 settings.templates.sort_by(|a, b| a.name.cmp(&b.name));
             }
             if settings.variables.len() > 1 {
                 settings.variables.sort_by(|a, b| a.name.cmp(&b.name));
             }
-            settings
+            Ok(settings)
         } else {
-            Settings {
-                central: None,
-                templates: vec![],
-                variables: vec![],
+            Ok(Settings {
+                file_path,
+                ..Default::default()
+            })
+        }
+    }
+
+    /// merge settings layer with higher precedence: templates and variables are overridden by name,
+    /// registries are appended, and other values are overridden if present
+    pub fn merge(&mut self, layer: Settings) {
+        let origin = layer.file_path.clone();
+        let mut merge_value =
+            |key: &str, value: &mut Option<String>, layer_value: Option<String>| {
+                if layer_value.is_some() {
+                    *value = layer_value;
+                    self.origins.insert(key.to_string(), origin.clone());
+                }
+            };
+        merge_value("central", &mut self.central, layer.central);
+        merge_value("github_api", &mut self.github_api, layer.github_api);
+        merge_value("github_token", &mut self.github_token, layer.github_token);
+        for project_dir in layer.trusted_projects.into_iter().flatten() {
+            let trusted_projects = self.trusted_projects.get_or_insert_with(Vec::new);
+            if !trusted_projects.contains(&project_dir) {
+                trusted_projects.push(project_dir);
             }
         }
+        for source in layer.registries.into_iter().flatten() {
+            let registries = self.registries.get_or_insert_with(Vec::new);
+            if !registries.contains(&source) {
+                self.origins
+                    .insert(format!("registry.{}", source), origin.clone());
+                registries.push(source);
+            }
+        }
+        for template in layer.templates {
+            self.origins
+                .insert(format!("template.{}", template.name), origin.clone());
+            self.templates.retain(|t| t.name != template.name);
+            self.templates.push(template);
+        }
+        for variable in layer.variables {
+            self.origins
+                .insert(format!("variable.{}", variable.name), origin.clone());
+            self.variables.retain(|v| v.name != variable.name);
+            self.variables.push(variable);
+        }
+        self.templates.sort_by(|a, b| a.name.cmp(&b.name));
+        self.variables.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// settings file of the value, such as `central`, `template.{name}` or `variable.{name}`
+    pub fn origin(&self, key: &str) -> Option<&Path> {
+        self.origins.get(key).map(PathBuf::as_path)
     }
 }
 
 impl Settings {
-    pub fn flush(&self) {
-        let home = env::var("HOME").unwrap();
-        let tgm_home = format!("{}/.tgm", home);
-        let tgm_path = Path::new(&tgm_home);
-        if !tgm_path.exists() {
-            std::fs::create_dir_all(tgm_path).unwrap();
+    /// save settings atomically: write temp file in the same directory, then rename it
+    pub fn flush(&self) -> Result<(), String> {
+        let write_error =
+            |e: std::io::Error| format!("Failed to write {}: {}", self.file_path.display(), e);
+        if let Some(parent) = self.file_path.parent() {
+            fs::create_dir_all(parent).map_err(write_error)?;
+        }
+        let format = FileFormat::from_path(&self.file_path.to_string_lossy());
+        let text = format.to_text(self)?;
+        let mut temp_file = self.file_path.clone().into_os_string();
+        temp_file.push(format!(".{}.tmp", std::process::id()));
+        fs::write(&temp_file, text).map_err(write_error)?;
+        fs::rename(&temp_file, &self.file_path).map_err(|e| {
+            fs::remove_file(&temp_file).unwrap_or_default();
+            write_error(e)
+        })
+    }
+
+    /// convert settings file to the format, and the old file is removed
+    pub fn convert(&mut self, format: FileFormat) -> Result<&Path, String> {
+        let old_path = self.file_path.clone();
+        self.file_path = old_path.with_extension(format.extension());
+        // new file is written by flush with temp file and rename, and the old file is kept if failed
+        if let Err(e) = self.flush() {
+            self.file_path = old_path;
+            return Err(e);
         }
-        let setting_json_path = home + "/.tgm/settings.json";
-        let mut file = File::create(Path::new(&setting_json_path)).unwrap();
-        let json_text = serde_json::to_string_pretty(self).unwrap();
-        file.write_all(json_text.as_bytes()).unwrap();
+        if old_path != self.file_path && old_path.exists() {
+            fs::remove_file(&old_path)
+                .map_err(|e| format!("Failed to remove {}: {}", old_path.display(), e))?;
+        }
+        Ok(&self.file_path)
     }
 
     pub fn find_template(&self, template_name: &str) -> Option<&Template> {
-        for template in self.templates.iter() {
-            if *template_name == template.name {
-                return Some(template);
-            }
+        self.templates
+            .iter()
+            .find(|template| template.name == template_name)
+    }
+
+    /// add template and save settings, and false if template exists
+    pub fn add_template(&mut self, template: Template) -> Result<bool, String> {
+        if self.find_template(&template.name).is_some() {
+            return Ok(false);
         }
-        None
+        self.templates.push(template);
+        self.flush()?;
+        Ok(true)
     }
 
-    pub fn add_template(&mut self, name: String, url: String, description: String) {
-        if self.find_template(&name).is_none() {
-            self.templates.push(Template {
-                name: name.clone(),
-                repository: url,
-                description,
-            });
-            self.flush();
-            println!("{} template added!", name);
-        } else {
-            println!("{} template already exits!", name);
+    /// remove template and save settings, and false if template not found
+    pub fn delete_template(&mut self, name: &str) -> Result<bool, String> {
+        if self.find_template(name).is_none() {
+            return Ok(false);
         }
+        self.templates.retain(|t| t.name != *name);
+        self.flush()?;
+        Ok(true)
     }
 
-    pub fn delete_template(&mut self, name: &str) {
-        if self.find_template(name).is_some() {
-            self.templates.retain(|t| t.name != *name);
-            self.flush();
-            println!("{} template removed!", name);
-        } else {
-            println!("{} template not found!", name);
+    /// add registry and save settings, and false if registry exists
+    pub fn add_registry(&mut self, source: &str) -> Result<bool, String> {
+        let registries = self.registries.get_or_insert_with(Vec::new);
+        if registries.iter().any(|registry| registry == source) {
+            return Ok(false);
         }
+        registries.push(source.to_string());
+        self.flush()?;
+        Ok(true)
+    }
+
+    /// remove registry and save settings, and false if registry not found
+    pub fn remove_registry(&mut self, source: &str) -> Result<bool, String> {
+        if let Some(registries) = self.registries.as_mut() {
+            if registries.iter().any(|registry| registry == source) {
+                registries.retain(|registry| registry != source);
+                if registries.is_empty() {
+                    self.registries = None;
+                }
+                self.flush()?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
     }
 
     pub fn find_variable_value(&self, name: &str) -> Option<String> {
@@ -155,6 +455,49 @@ impl Settings {
         None
     }
 
+    /// value of global variable, and `env:`, `file:` and `cmd:` references are resolved for secret variables only.
+    /// `cmd:` references from project settings are refused unless the project is in `trusted_projects`.
+    pub fn resolve_variable_value(&self, name: &str) -> Result<Option<String>, String> {
+        let Some(variable) = self.variables.iter().find(|v| v.name == name) else {
+            return Ok(None);
+        };
+        let Some(value) = variable.value.clone() else {
+            return Ok(None);
+        };
+        if !variable.secret {
+            return Ok(Some(value));
+        }
+        if value.starts_with("cmd:") && !self.is_trusted_origin(&format!("variable.{}", name)) {
+            return Err(format!(
+                "cmd: reference from untrusted project settings {}, please add the project with 'tgm config set trusted_projects <dir>'",
+                self.origin(&format!("variable.{}", name)).unwrap_or(Path::new("")).display()
+            ));
+        }
+        secret::resolve(&value).map(Some)
+    }
+
+    /// whether the value came from system or user settings, or from settings of a trusted project
+    fn is_trusted_origin(&self, key: &str) -> bool {
+        let Some(project_file) = &self.project_file else {
+            return true;
+        };
+        if self.origin(key) != Some(project_file.as_path()) {
+            return true;
+        }
+        // project directory is the parent of .tgm directory
+        let Some(project_dir) = project_file
+            .parent()
+            .and_then(Path::parent)
+            .and_then(|dir| dir.canonicalize().ok())
+        else {
+            return false;
+        };
+        self.trusted_projects
+            .iter()
+            .flatten()
+            .any(|dir| Path::new(dir).canonicalize().ok().as_ref() == Some(&project_dir))
+    }
+
     pub fn set_variable(&mut self, name: &str, value: &str, description: &str) {
         for variable in self.variables.iter_mut() {
             if variable.name == name {
@@ -167,25 +510,103 @@ impl Settings {
             name: String::from(name),
             value: Some(String::from(value)),
             description: String::from(description),
-            pattern: None,
+            ..Default::default()
         });
     }
+
+    /// value of settings key, such as `central`, `github_api`, `github_token` and `registries`, or global variable
+    pub fn get_value(&self, key: &str) -> Option<String> {
+        match key {
+            "central" => self.central.clone(),
+            "github_api" => self.github_api.clone(),
+            "github_token" => self.github_token.clone(),
+            "registries" => self
+                .registries
+                .as_ref()
+                .map(|registries| registries.join(",")),
+            "trusted_projects" => self
+                .trusted_projects
+                .as_ref()
+                .map(|trusted_projects| trusted_projects.join(",")),
+            _ => self.find_variable_value(key),
+        }
+    }
+
+    /// value of settings key for output, and `github_token` and secret variables are masked
+    pub fn get_display_value(&self, key: &str) -> Option<String> {
+        let value = self.get_value(key)?;
+        if key == "github_token" {
+            return Some(secret::MASK.to_string());
+        }
+        match self.variables.iter().find(|variable| variable.name == key) {
+            Some(variable) => Some(variable.display_value(&value)),
+            None => Some(value),
+        }
+    }
+
+    /// set settings key or global variable, and registries are separated by comma
+    pub fn set_value(&mut self, key: &str, value: &str, description: Option<&str>) {
+        match key {
+            "central" => self.central = Some(value.to_string()),
+            "github_api" => self.github_api = Some(value.to_string()),
+            "github_token" => self.github_token = Some(value.to_string()),
+            "registries" => self.registries = split_values(value),
+            "trusted_projects" => self.trusted_projects = split_values(value),
+            _ => {
+                let description = description.map(String::from).unwrap_or_else(|| {
+                    self.variables
+                        .iter()
+                        .find(|variable| variable.name == key)
+                        .map(|variable| variable.description.clone())
+                        .unwrap_or_default()
+                });
+                self.set_variable(key, value, &description);
+            }
+        }
+    }
+
+    /// remove settings key or global variable, and false if absent
+    pub fn unset_value(&mut self, key: &str) -> bool {
+        match key {
+            "central" => self.central.take().is_some(),
+            "github_api" => self.github_api.take().is_some(),
+            "github_token" => self.github_token.take().is_some(),
+            "registries" => self.registries.take().is_some(),
+            "trusted_projects" => self.trusted_projects.take().is_some(),
+            _ => {
+                let count = self.variables.len();
+                self.variables.retain(|variable| variable.name != key);
+                self.variables.len() != count
+            }
+        }
+    }
 }
 
 impl AppTemplate {
-    pub fn new(template_json_file: &str) -> AppTemplate {
-        let path = Path::new(template_json_file);
-        if path.exists() {
-            let setting_json =
-                fs::read_to_string(template_json_file).expect("Failed to read template.json");
-            serde_json::from_str(setting_json.as_str()).unwrap()
-        } else {
-            AppTemplate::default()
+    /// load template.json, template.yaml, template.yml or template.toml in the directory by precedence order,
+    /// and default template if file absent
+    pub fn from_dir(template_dir: &Path) -> Result<AppTemplate, String> {
+        match find_file(template_dir, TEMPLATE_FILE_NAMES) {
+            Some(path) => AppTemplate::from_file(&path),
+            None => Ok(AppTemplate::default()),
         }
     }
 
-    pub fn with_remote(url: &str) -> reqwest::Result<AppTemplate> {
-        reqwest::blocking::get(url)?.json::<AppTemplate>()
+    /// load template file, and format is decided by extension
+    pub fn from_file(template_file: &Path) -> Result<AppTemplate, String> {
+        let text = fs::read_to_string(template_file)
+            .map_err(|e| format!("Failed to read {}: {}", template_file.display(), e))?;
+        FileFormat::from_path(&template_file.to_string_lossy())
+            .parse(&text)
+            .map_err(|e| format!("{}: {}", template_file.display(), e))
+    }
+
+    pub fn with_remote(url: &str) -> Result<AppTemplate, String> {
+        let text = reqwest::blocking::get(url)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map_err(|e| e.to_string())?;
+        FileFormat::from_path(url).parse(&text)
     }
 }
 
@@ -195,9 +616,12 @@ impl Default for AppTemplate {
             name: String::from("unknown"),
             description: String::from("not available"),
             repository: String::from("not available"),
+            schema_version: None,
+            tags: None,
+            license: None,
             variables: Option::None,
             files: Option::None,
-            post_create: Some(String::from("Desc absent")),
+            post_create: None,
         }
     }
 }
@@ -208,37 +632,184 @@ mod tests {
 
     #[test]
     fn test_load() {
-        let settings = Settings::load();
+        paths::use_test_home();
+        let settings = Settings::load().unwrap();
         println!("{:?}", settings);
         assert!(!settings.templates.is_empty());
     }
 
     #[test]
     fn test_find_template() {
-        let settings = Settings::load();
+        paths::use_test_home();
+        let settings = Settings::load().unwrap();
         let template_name = "spring-boot-java";
-        let template = settings.find_template(&template_name).unwrap();
+        let template = settings.find_template(template_name).unwrap();
         println!("template description: {}", template.description);
     }
 
+    #[test]
+    fn test_merge_settings() {
+        let layer = |file_name: &str, json_text: &str| {
+            let mut settings: Settings = serde_json::from_str(json_text).unwrap();
+            settings.file_path = PathBuf::from(file_name);
+            settings
+        };
+        let mut settings = Settings::default();
+        settings.merge(layer(
+            "/etc/tgm/settings.json",
+            r#"{"central": "acme-templates", "registries": ["https://example.com/index.json"],
+                "variables": [{"name": "author_name", "value": "acme", "description": ""}]}"#,
+        ));
+        settings.merge(layer(
+            "user/settings.json",
+            r#"{"templates": [{"name": "demo", "repository": "https://github.com/acme/demo", "description": ""}],
+                "variables": [{"name": "author_name", "value": "jackie", "description": ""}]}"#,
+        ));
+        settings.merge(layer(
+            "project/.tgm/settings.json",
+            r#"{"central": "monorepo-templates",
+                "templates": [{"name": "demo", "repository": "https://github.com/monorepo/demo", "description": ""}]}"#,
+        ));
+        assert_eq!(settings.central.as_deref(), Some("monorepo-templates"));
+        assert_eq!(
+            settings.find_template("demo").unwrap().repository,
+            "https://github.com/monorepo/demo"
+        );
+        assert_eq!(
+            settings.find_variable_value("author_name").unwrap(),
+            "jackie"
+        );
+        assert_eq!(settings.registries.as_ref().unwrap().len(), 1);
+        assert_eq!(
+            settings.origin("template.demo"),
+            Some(Path::new("project/.tgm/settings.json"))
+        );
+        assert_eq!(
+            settings.origin("variable.author_name"),
+            Some(Path::new("user/settings.json"))
+        );
+        assert_eq!(
+            settings.origin("registry.https://example.com/index.json"),
+            Some(Path::new("/etc/tgm/settings.json"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_variable_value() {
+        let project_dir = std::env::temp_dir().join(format!("tgm-project-{}", std::process::id()));
+        fs::create_dir_all(project_dir.join(".tgm")).unwrap();
+        let project_file = project_dir.join(".tgm/settings.json");
+        let mut settings = Settings::default();
+        let mut project_settings: Settings = serde_json::from_str(
+            r#"{"variables": [{"name": "token", "value": "cmd:echo s3cr3t", "description": "", "secret": true},
+                {"name": "greeting", "value": "cmd:echo hello", "description": ""}]}"#,
+        )
+        .unwrap();
+        project_settings.file_path = project_file.clone();
+        settings.merge(project_settings);
+        settings.project_file = Some(project_file);
+        // plain value for variable not marked secret
+        assert_eq!(
+            settings
+                .resolve_variable_value("greeting")
+                .unwrap()
+                .unwrap(),
+            "cmd:echo hello"
+        );
+        assert!(settings.resolve_variable_value("token").is_err());
+        settings.trusted_projects = Some(vec![project_dir.display().to_string()]);
+        let value = settings.resolve_variable_value("token");
+        fs::remove_dir_all(&project_dir).unwrap();
+        assert_eq!(value.unwrap().unwrap(), "s3cr3t");
+        assert!(settings.resolve_variable_value("absent").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_config_values() {
+        let file_path =
+            std::env::temp_dir().join(format!("tgm-settings-{}.json", std::process::id()));
+        let mut settings = Settings::load_from(file_path.clone()).unwrap();
+        settings.set_value("central", "acme-templates", None);
+        settings.set_value("registries", "a.json, b.toml", None);
+        settings.set_value("author_name", "jackie", Some("author's name"));
+        settings.set_value("author_name", "linux_china", None);
+        settings.flush().unwrap();
+        let mut settings = Settings::load_from(file_path.clone()).unwrap();
+        assert_eq!(settings.get_value("central").unwrap(), "acme-templates");
+        assert_eq!(settings.get_value("registries").unwrap(), "a.json,b.toml");
+        assert_eq!(settings.get_value("author_name").unwrap(), "linux_china");
+        assert_eq!(settings.variables[0].description, "author's name");
+        settings.set_value("github_token", "ghp_s3cr3t", None);
+        settings.set_value("npm_token", "s3cr3t", None);
+        settings.variables[1].secret = true;
+        assert_eq!(
+            settings.get_display_value("github_token").unwrap(),
+            secret::MASK
+        );
+        assert_eq!(
+            settings.get_display_value("npm_token").unwrap(),
+            secret::MASK
+        );
+        assert_eq!(
+            settings.get_display_value("author_name").unwrap(),
+            "linux_china"
+        );
+        assert!(settings.unset_value("author_name"));
+        assert!(!settings.unset_value("author_name"));
+        assert!(settings.unset_value("registries"));
+        fs::remove_file(&file_path).unwrap();
+        assert!(settings.get_value("registries").is_none());
+    }
+
+    #[test]
+    fn test_convert_settings() {
+        let file_path =
+            std::env::temp_dir().join(format!("tgm-convert-{}.json", std::process::id()));
+        let mut settings = Settings::load_from(file_path.clone()).unwrap();
+        settings.set_value("central", "acme-templates", None);
+        settings.flush().unwrap();
+        let toml_file = settings.convert(FileFormat::Toml).unwrap().to_path_buf();
+        let settings = Settings::load_from(toml_file.clone()).unwrap();
+        fs::remove_file(&toml_file).unwrap();
+        assert!(!file_path.exists());
+        assert_eq!(settings.central.as_deref(), Some("acme-templates"));
+    }
+
+    #[test]
+    fn test_template_repo_source() {
+        let template = Template {
+            name: String::from("spring"),
+            repository: String::from("https://github.com/acme/templates.git"),
+            git_ref: Some(String::from("v1.0")),
+            subdir: Some(String::from("java/spring")),
+            ..Default::default()
+        };
+        let repo_source = template.repo_source().unwrap();
+        assert_eq!(
+            repo_source.raw_url("v1.0", "template.json"),
+            "https://raw.githubusercontent.com/acme/templates/v1.0/java/spring/template.json"
+        );
+    }
+
     #[test]
     fn test_app_template() {
-        let app_template_file = "temp/demo/template.json";
-        let app_template = AppTemplate::new(&app_template_file);
+        let app_template = AppTemplate::from_dir(Path::new("temp/demo"));
         println!("{:?}", app_template);
     }
 
     #[test]
     fn test_fetch_remote_template() -> reqwest::Result<()> {
         let url = "https://gist.githubusercontent.com/linux-china/50d0ad9db30489951dc66ecfa4fe2785/raw/8cef649356a4b073e4d55e0221eff97f31133522/template.json";
-        let app_template = AppTemplate::with_remote(&url).unwrap();
+        let app_template = AppTemplate::with_remote(url).unwrap();
         println!("{:?}", app_template);
         Ok(())
     }
 
     #[test]
-    fn test_github_repos() -> reqwest::Result<()> {
-        let repos = GithubRepo::fetch_tgm_template_repos("tgm-templates")?;
+    fn test_github_repos() -> Result<(), String> {
+        paths::use_test_home();
+        let api = GithubApi::from_settings(&Settings::load()?);
+        let repos = GithubRepo::fetch_tgm_template_repos(&api, "tgm-templates")?;
         println!("{:?}", repos);
         Ok(())
     }
@@ -0,0 +1,25 @@
+//! output format for list, search, config and info commands
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    pub fn is_table(&self) -> bool {
+        *self == OutputFormat::Table
+    }
+
+    /// print value as json or yaml, and nothing for table
+    pub fn print<T: Serialize + ?Sized>(&self, value: &T) {
+        match self {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value).unwrap()),
+            OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value).unwrap()),
+            OutputFormat::Table => {}
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! tgm directories: `TGM_HOME`, XDG base directories and platform defaults
+use crate::format::{find_file, SETTINGS_FILE_NAMES};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static CONFIG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// use the settings file from `--config` option instead of settings in config directory
+pub fn set_config_file(config_file: PathBuf) {
+    CONFIG_FILE.set(config_file).unwrap_or_default();
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// home directory, and None if absent
+pub fn home_dir() -> Option<PathBuf> {
+    dirs::home_dir()
+}
+
+/// config directory by precedence: `TGM_HOME`, `$XDG_CONFIG_HOME/tgm`, existing `~/.tgm`,
+/// then platform config directory, such as `~/.config/tgm` on Linux and `~/Library/Application Support/tgm` on macOS
+pub fn config_dir() -> PathBuf {
+    if let Some(tgm_home) = env_dir("TGM_HOME") {
+        return tgm_home;
+    }
+    if let Some(xdg_config_home) = env_dir("XDG_CONFIG_HOME") {
+        return xdg_config_home.join("tgm");
+    }
+    let legacy_dir = home_dir().map(|home| home.join(".tgm"));
+    if let Some(legacy_dir) = legacy_dir.as_ref().filter(|dir| dir.is_dir()) {
+        return legacy_dir.clone();
+    }
+    dirs::config_dir()
+        .map(|config_dir| config_dir.join("tgm"))
+        .or(legacy_dir)
+        .unwrap_or_else(|| PathBuf::from(".tgm"))
+}
+
+/// cache directory by precedence: `$TGM_HOME/cache`, `$XDG_CACHE_HOME/tgm`, then platform cache directory
+pub fn cache_dir() -> PathBuf {
+    if let Some(tgm_home) = env_dir("TGM_HOME") {
+        return tgm_home.join("cache");
+    }
+    if let Some(xdg_cache_home) = env_dir("XDG_CACHE_HOME") {
+        return xdg_cache_home.join("tgm");
+    }
+    dirs::cache_dir()
+        .map(|cache_dir| cache_dir.join("tgm"))
+        .unwrap_or_else(|| config_dir().join("cache"))
+}
+
+/// settings file: `--config` option, or settings.json, settings.yaml, settings.yml or settings.toml in config directory
+pub fn settings_file() -> PathBuf {
+    if let Some(config_file) = CONFIG_FILE.get() {
+        return config_file.clone();
+    }
+    let config_dir = config_dir();
+    find_file(&config_dir, SETTINGS_FILE_NAMES).unwrap_or_else(|| config_dir.join("settings.json"))
+}
+
+/// system config directory: `/etc/tgm` on Unix, `%PROGRAMDATA%\tgm` on Windows
+pub fn system_config_dir() -> PathBuf {
+    if cfg!(windows) {
+        env_dir("PROGRAMDATA")
+            .unwrap_or_else(|| PathBuf::from("C:\\ProgramData"))
+            .join("tgm")
+    } else {
+        PathBuf::from("/etc/tgm")
+    }
+}
+
+/// system settings file, and None if absent
+pub fn system_settings_file() -> Option<PathBuf> {
+    find_file(&system_config_dir(), SETTINGS_FILE_NAMES)
+}
+
+/// project settings file in `.tgm` directory, discovered by walking up from current directory to home directory
+pub fn project_settings_file() -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok()?;
+    find_project_settings_file(&current_dir, home_dir().as_deref(), &config_dir())
+}
+
+/// walk up from `current_dir` and stop at `home`, and `~/.tgm` and user config directory are never projects
+fn find_project_settings_file(
+    current_dir: &Path,
+    home: Option<&Path>,
+    user_dir: &Path,
+) -> Option<PathBuf> {
+    let home = home.map(|home| home.canonicalize().unwrap_or_else(|_| home.to_path_buf()));
+    let user_dir = user_dir.canonicalize().ok();
+    let current_dir = current_dir
+        .canonicalize()
+        .unwrap_or_else(|_| current_dir.to_path_buf());
+    current_dir
+        .ancestors()
+        .take_while(|dir| Some(*dir) != home.as_deref())
+        .map(|dir| dir.join(".tgm"))
+        .filter(|dir| dir.canonicalize().ok() != user_dir)
+        .find_map(|dir| find_file(&dir, SETTINGS_FILE_NAMES))
+}
+
+/// use temp directory with settings from tgm_data as `TGM_HOME`, and tests never read the real settings
+#[doc(hidden)]
+pub fn use_test_home() {
+    static TEST_HOME: OnceLock<PathBuf> = OnceLock::new();
+    TEST_HOME.get_or_init(|| {
+        let test_home = env::temp_dir().join(format!("tgm-home-{}", std::process::id()));
+        std::fs::create_dir_all(&test_home).unwrap();
+        std::fs::copy("tgm_data/settings.json", test_home.join("settings.json")).unwrap();
+        env::set_var("TGM_HOME", &test_home);
+        test_home
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_dir() {
+        use_test_home();
+        let tgm_home = env_dir("TGM_HOME").unwrap();
+        assert_eq!(config_dir(), tgm_home);
+        assert_eq!(cache_dir(), tgm_home.join("cache"));
+        assert_eq!(settings_file(), tgm_home.join("settings.json"));
+    }
+
+    #[test]
+    fn test_project_settings_file() {
+        let home = env::temp_dir().join(format!("tgm-project-home-{}", std::process::id()));
+        let project = home.join("work").join("demo");
+        let src = project.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::create_dir_all(home.join(".tgm")).unwrap();
+        std::fs::write(home.join(".tgm").join("settings.json"), "{}").unwrap();
+        let user_dir = home.join("tgm-home");
+        // legacy ~/.tgm is not a project even if TGM_HOME is elsewhere
+        assert_eq!(
+            find_project_settings_file(&src, Some(&home), &user_dir),
+            None
+        );
+        std::fs::create_dir_all(project.join(".tgm")).unwrap();
+        std::fs::write(project.join(".tgm").join("settings.json"), "{}").unwrap();
+        let settings_file = find_project_settings_file(&src, Some(&home), &user_dir).unwrap();
+        assert!(settings_file.ends_with("demo/.tgm/settings.json"));
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
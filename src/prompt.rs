@@ -0,0 +1,165 @@
+//! interactive prompts on terminal: fuzzy template picker and form-style variable input
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{FuzzySelect, Input, Password, Select};
+use std::io::IsTerminal;
+use std::path::Path;
+use tgm::models::Variable;
+use tgm::search::Candidate;
+
+/// prompts are interactive only if both stdin and stderr are terminals, and piped input is read line by line
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// candidates for picker with display items, and template name is unique with the first candidate kept.
+/// Candidates are ordered as template lookup: local, registries and remote.
+pub fn picker_items(candidates: &[Candidate]) -> Vec<(&Candidate, String)> {
+    let mut items: Vec<(&Candidate, String)> = vec![];
+    for candidate in candidates.iter() {
+        let template = &candidate.template;
+        if items.iter().any(|(c, _)| c.template.name == template.name) {
+            continue;
+        }
+        let item = if template.description.is_empty() {
+            format!("{} ({})", template.name, candidate.source)
+        } else {
+            format!(
+                "{} - {} ({})",
+                template.name, template.description, candidate.source
+            )
+        };
+        items.push((candidate, item));
+    }
+    items
+}
+
+/// pick template with fuzzy search, and None if canceled by Esc or q
+pub fn pick_template(candidates: &[Candidate]) -> Result<Option<String>, String> {
+    let items = picker_items(candidates);
+    if items.is_empty() {
+        return Err("No template found, please add template or registry first".to_string());
+    }
+    let labels: Vec<&str> = items.iter().map(|(_, item)| item.as_str()).collect();
+    let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Pick a template")
+        .items(&labels)
+        .default(0)
+        .interact_opt()
+        .map_err(|e| e.to_string())?;
+    Ok(selection.map(|index| items[index].0.template.name.clone()))
+}
+
+/// input app's directory, and the directory should not exist
+pub fn input_app_dir(default_dir: &str) -> Result<String, String> {
+    Input::<String>::with_theme(&ColorfulTheme::default())
+        .with_prompt("App's directory")
+        .default(default_dir.to_string())
+        .validate_with(|dir: &String| {
+            if Path::new(dir).exists() {
+                Err(format!("'{}' directory exists already", dir))
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()
+        .map_err(|e| e.to_string())
+}
+
+/// choices of variable, and boolean variable has true and false choices
+pub fn variable_choices(v: &Variable) -> Option<Vec<String>> {
+    match &v.choices {
+        Some(choices) => Some(choices.clone()),
+        None if v.type_name() == "boolean" => Some(vec!["true".to_string(), "false".to_string()]),
+        None => None,
+    }
+}
+
+/// input variable value: arrow selection for choices, hidden input for secret, otherwise text input with default.
+/// Value is validated inline with regex pattern, choices and variable type.
+pub fn input_variable(v: &Variable, default_value: &str, secret: bool) -> Result<String, String> {
+    let theme = ColorfulTheme::default();
+    let prompt = if v.description.is_empty() {
+        v.name.clone()
+    } else {
+        format!("{} ({})", v.name, v.description)
+    };
+    let value = if secret {
+        Password::with_theme(&theme)
+            .with_prompt(prompt)
+            .validate_with(|value: &String| v.validate(value))
+            .interact()
+    } else if let Some(choices) = variable_choices(v) {
+        let default_index = choices
+            .iter()
+            .position(|choice| choice == default_value)
+            .unwrap_or(0);
+        Select::with_theme(&theme)
+            .with_prompt(prompt)
+            .items(&choices)
+            .default(default_index)
+            .interact()
+            .map(|index| choices[index].clone())
+    } else {
+        let mut input = Input::<String>::with_theme(&theme)
+            .with_prompt(prompt)
+            .allow_empty(true)
+            .validate_with(|value: &String| v.validate(value));
+        if !default_value.is_empty() {
+            input = input.default(default_value.to_string());
+        }
+        input.interact_text()
+    };
+    value
+        .map(|value| value.trim().to_string())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgm::models::Template;
+
+    #[test]
+    fn test_picker_items() {
+        let candidate = |source: &str, name: &str, description: &str| Candidate {
+            source: source.to_string(),
+            template: Template {
+                name: name.to_string(),
+                description: description.to_string(),
+                ..Default::default()
+            },
+        };
+        let candidates = vec![
+            candidate("local", "spring-boot", "Spring Boot app"),
+            candidate("remote", "spring-boot", "Spring Boot template"),
+            candidate("remote", "vue", ""),
+        ];
+        let items: Vec<String> = picker_items(&candidates)
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect();
+        assert_eq!(
+            items,
+            vec!["spring-boot - Spring Boot app (local)", "vue (remote)"]
+        );
+        let level = Variable {
+            name: "level".to_string(),
+            choices: Some(vec!["debug".to_string(), "info".to_string()]),
+            ..Default::default()
+        };
+        assert!(level.validate("info").is_ok());
+        assert_eq!(
+            level.validate("warn").unwrap_err(),
+            "should be one of debug, info"
+        );
+        let enabled = Variable {
+            name: "enabled".to_string(),
+            var_type: Some("boolean".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            variable_choices(&enabled).unwrap(),
+            vec!["true".to_string(), "false".to_string()]
+        );
+    }
+}
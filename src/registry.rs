@@ -0,0 +1,412 @@
+//! Static template registry: an index file (JSON or TOML) listing templates, hosted on any http server or file system
+use crate::format::{find_file, TEMPLATE_FILE_NAMES};
+use crate::models::{AppTemplate, Template};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path};
+
+/// current registry index format version
+pub const INDEX_VERSION: u32 = 1;
+/// latest template.json schema version understood by tgm
+pub const TEMPLATE_SCHEMA_VERSION: u32 = 1;
+
+/// Registry index, such as index.json or index.toml
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryIndex {
+    pub version: u32,
+    #[serde(default)]
+    pub templates: Vec<RegistryEntry>,
+    /// custom licenses, such as company's proprietary license
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<RegistryLicense>,
+}
+
+/// License entry in registry index
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryLicense {
+    pub id: String,
+    pub name: String,
+    /// license text url or path, relative to the index
+    pub url: String,
+}
+
+/// Template entry in registry index
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryEntry {
+    pub name: String,
+    pub repository: String,
+    /// git branch or tag to clone
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// sub directory of template in repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// template.json schema version of the template
+    pub schema_version: u32,
+}
+
+impl RegistryIndex {
+    /// load registry index from local path or http(s) url, and validate it
+    pub fn load(source: &str) -> Result<RegistryIndex, String> {
+        let text = read_source(source)?;
+        let index = RegistryIndex::parse(&text, is_toml(source))
+            .map_err(|e| format!("Illegal registry index {}: {}", source, e))?;
+        index.validate().map_err(|errors| {
+            format!("Illegal registry index {}: {}", source, errors.join("; "))
+        })?;
+        Ok(index)
+    }
+
+    pub fn parse(text: &str, toml_format: bool) -> Result<RegistryIndex, String> {
+        if toml_format {
+            toml::from_str(text).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(text).map_err(|e| e.to_string())
+        }
+    }
+
+    /// check index with the rules of `schemas/registry.schema.json`, and return all violations.
+    /// The schema is documentation for editors and CI, and `test_schema_in_sync` keeps both in sync.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors: Vec<String> = vec![];
+        if self.version != INDEX_VERSION {
+            errors.push(format!(
+                "unsupported index version {}, expected {}",
+                self.version, INDEX_VERSION
+            ));
+        }
+        let mut names = HashSet::new();
+        for (i, entry) in self.templates.iter().enumerate() {
+            if !is_legal_name(&entry.name) {
+                errors.push(format!("templates[{}]: illegal name '{}'", i, entry.name));
+            } else if !names.insert(entry.name.as_str()) {
+                errors.push(format!("templates[{}]: duplicate name '{}'", i, entry.name));
+            }
+            if entry.repository.trim().is_empty() {
+                errors.push(format!("templates[{}]: repository is empty", i));
+            }
+            if entry.schema_version == 0 || entry.schema_version > TEMPLATE_SCHEMA_VERSION {
+                errors.push(format!(
+                    "templates[{}]: unsupported schema_version {}",
+                    i, entry.schema_version
+                ));
+            }
+        }
+        let mut ids = HashSet::new();
+        for (i, license) in self.licenses.iter().enumerate() {
+            if !is_legal_name(&license.id) {
+                errors.push(format!("licenses[{}]: illegal id '{}'", i, license.id));
+            } else if !ids.insert(license.id.as_str()) {
+                errors.push(format!("licenses[{}]: duplicate id '{}'", i, license.id));
+            }
+            if license.url.trim().is_empty() {
+                errors.push(format!("licenses[{}]: url is empty", i));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// build registry index from a directory with template sub directories, each one with template.json
+    pub fn build(templates_dir: &str) -> Result<RegistryIndex, String> {
+        let dir = Path::new(templates_dir);
+        let mut template_dirs = vec![dir.to_path_buf()];
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", templates_dir, e))?;
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                template_dirs.push(entry.path());
+            }
+        }
+        let mut templates = vec![];
+        for template_dir in template_dirs {
+            let Some(template_file) = find_file(&template_dir, TEMPLATE_FILE_NAMES) else {
+                continue;
+            };
+            let app_template = AppTemplate::from_file(&template_file)?;
+            templates.push(RegistryEntry {
+                name: app_template.name,
+                repository: app_template.repository,
+                git_ref: read_git_branch(&template_dir),
+                subdir: None,
+                description: app_template.description,
+                tags: app_template.tags.unwrap_or_default(),
+                schema_version: app_template.schema_version.unwrap_or(1),
+            });
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        let index = RegistryIndex {
+            version: INDEX_VERSION,
+            templates,
+            licenses: vec![],
+        };
+        index
+            .validate()
+            .map_err(|errors| errors.join("; "))
+            .map(|_| index)
+    }
+
+    pub fn to_text(&self, toml_format: bool) -> String {
+        if toml_format {
+            toml::to_string_pretty(self).unwrap()
+        } else {
+            serde_json::to_string_pretty(self).unwrap()
+        }
+    }
+
+    pub fn find_template(&self, template_name: &str) -> Option<&RegistryEntry> {
+        self.templates
+            .iter()
+            .find(|entry| entry.name == template_name)
+    }
+}
+
+impl RegistryEntry {
+    pub fn to_template(&self) -> Template {
+        Template {
+            name: self.name.clone(),
+            repository: self.repository.clone(),
+            git_ref: self.git_ref.clone(),
+            subdir: self.subdir.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+}
+
+/// read text from local path or http(s) url
+pub fn read_source(source: &str) -> Result<String, String> {
+    if is_url(source) {
+        reqwest::blocking::get(source)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map_err(|e| format!("Failed to fetch {}: {}", source, e))
+    } else {
+        fs::read_to_string(source).map_err(|e| format!("Failed to read {}: {}", source, e))
+    }
+}
+
+/// resolve url or path relative to the index source.
+/// Remote index can't refer to local files, and relative path can't escape the directory of index.
+pub fn resolve_url(source: &str, url: &str) -> Result<String, String> {
+    if is_url(url) {
+        return Ok(url.to_string());
+    }
+    let path = Path::new(url);
+    let is_absolute = path.is_absolute() || path.has_root();
+    if is_url(source) && is_absolute {
+        return Err(format!(
+            "{} of remote registry {} is a local path",
+            url, source
+        ));
+    }
+    if is_absolute {
+        return Ok(url.to_string());
+    }
+    let mut depth = 0;
+    for component in path.components() {
+        match component {
+            Component::ParentDir if depth == 0 => {
+                return Err(format!(
+                    "{} escapes the directory of registry {}",
+                    url, source
+                ))
+            }
+            Component::ParentDir => depth -= 1,
+            Component::Normal(_) => depth += 1,
+            _ => {}
+        }
+    }
+    if is_url(source) {
+        let base = source.rsplit_once('/').map_or(source, |(base, _)| base);
+        Ok(format!("{}/{}", base, url))
+    } else {
+        let base = Path::new(source).parent().unwrap_or(Path::new(""));
+        Ok(base.join(url).display().to_string())
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// index format is decided by file extension: .toml for TOML, others for JSON
+pub fn is_toml(source: &str) -> bool {
+    source.ends_with(".toml")
+}
+
+fn is_legal_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// current branch name from .git/HEAD
+fn read_git_branch(dir: &Path) -> Option<String> {
+    let head = fs::read_to_string(dir.join(".git").join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml_index() {
+        let text = r#"
+version = 1
+
+[[templates]]
+name = "spring-boot-java"
+repository = "https://git.example.com/templates/spring-boot-java.git"
+ref = "main"
+description = "Spring Boot App template with Java"
+tags = ["java", "spring"]
+schema_version = 1
+
+[[licenses]]
+id = "ACME-Proprietary"
+name = "ACME Proprietary License"
+url = "licenses/acme.txt"
+"#;
+        let index = RegistryIndex::parse(text, true).unwrap();
+        assert!(index.validate().is_ok());
+        let entry = index.find_template("spring-boot-java").unwrap();
+        assert_eq!(entry.git_ref.as_deref(), Some("main"));
+        assert_eq!(entry.tags, vec!["java", "spring"]);
+        let url = &index.licenses[0].url;
+        assert_eq!(
+            resolve_url("https://templates.example.com/index.toml", url).unwrap(),
+            "https://templates.example.com/licenses/acme.txt"
+        );
+        assert_eq!(
+            resolve_url("/srv/index.toml", url).unwrap(),
+            "/srv/licenses/acme.txt"
+        );
+        let remote = "https://templates.example.com/index.json";
+        assert!(resolve_url(remote, "/home/u/.ssh/id_rsa").is_err());
+        assert!(resolve_url(remote, "../secret.txt").is_err());
+        assert!(resolve_url("/srv/index.toml", "licenses/../../etc/passwd").is_err());
+        assert_eq!(
+            resolve_url("/srv/index.toml", "licenses/../acme.txt").unwrap(),
+            "/srv/licenses/../acme.txt"
+        );
+    }
+
+    #[test]
+    fn test_validate_index() {
+        let text = r#"{
+  "version": 1,
+  "templates": [
+    {"name": "demo", "repository": "", "description": "demo", "schema_version": 1},
+    {"name": "demo", "repository": "https://example.com/demo.git", "description": "demo", "schema_version": 9}
+  ]
+}"#;
+        let index = RegistryIndex::parse(text, false).unwrap();
+        let errors = index.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(RegistryIndex::parse(r#"{"version": 1, "items": []}"#, false).is_err());
+    }
+
+    /// schemas/registry.schema.json documents the index format, and must match the models and validate rules
+    #[test]
+    fn test_schema_in_sync() {
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../schemas/registry.schema.json")).unwrap();
+        let index = RegistryIndex {
+            version: INDEX_VERSION,
+            templates: vec![RegistryEntry {
+                name: String::from("demo"),
+                repository: String::from("https://example.com/demo.git"),
+                git_ref: Some(String::from("main")),
+                subdir: Some(String::from("demo")),
+                description: String::from("demo"),
+                tags: vec![String::from("demo")],
+                schema_version: TEMPLATE_SCHEMA_VERSION,
+            }],
+            licenses: vec![RegistryLicense {
+                id: String::from("demo"),
+                name: String::from("demo"),
+                url: String::from("demo.txt"),
+            }],
+        };
+        let value = serde_json::to_value(&index).unwrap();
+        let keys = |value: &serde_json::Value| {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        let template_schema = &schema["properties"]["templates"]["items"];
+        let license_schema = &schema["properties"]["licenses"]["items"];
+        assert_eq!(keys(&schema["properties"]), keys(&value));
+        assert_eq!(
+            keys(&template_schema["properties"]),
+            keys(&value["templates"][0])
+        );
+        assert_eq!(
+            keys(&license_schema["properties"]),
+            keys(&value["licenses"][0])
+        );
+        assert_eq!(schema["properties"]["version"]["const"], INDEX_VERSION);
+        assert_eq!(
+            template_schema["properties"]["schema_version"]["maximum"],
+            TEMPLATE_SCHEMA_VERSION
+        );
+        assert_required::<RegistryIndex>(&schema, &value);
+        assert_required::<RegistryEntry>(template_schema, &value["templates"][0]);
+        assert_required::<RegistryLicense>(license_schema, &value["licenses"][0]);
+        for (item_schema, field) in [(template_schema, "name"), (license_schema, "id")] {
+            let pattern = item_schema["properties"][field]["pattern"]
+                .as_str()
+                .unwrap();
+            let regex = regex::Regex::new(pattern).unwrap();
+            for name in ["spring-boot_java.2", "", "a b", "demo/app", "中文"] {
+                assert_eq!(regex.is_match(name), is_legal_name(name), "{}", name);
+            }
+        }
+    }
+
+    /// every required field in schema must be required by serde too
+    fn assert_required<T: serde::de::DeserializeOwned>(
+        schema: &serde_json::Value,
+        value: &serde_json::Value,
+    ) {
+        let required = schema["required"].as_array().unwrap();
+        for field in required.iter().map(|field| field.as_str().unwrap()) {
+            let mut value = value.clone();
+            value.as_object_mut().unwrap().remove(field);
+            assert!(serde_json::from_value::<T>(value).is_err(), "{}", field);
+        }
+        let optional: Vec<&String> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .filter(|key| !required.iter().any(|field| field == *key))
+            .collect();
+        for field in optional {
+            let mut value = value.clone();
+            value.as_object_mut().unwrap().remove(field);
+            assert!(serde_json::from_value::<T>(value).is_ok(), "{}", field);
+        }
+    }
+
+    #[test]
+    fn test_build_index() {
+        let index = RegistryIndex::build("tgm_data").unwrap();
+        let entry = index.find_template("spring-boot-java").unwrap();
+        assert_eq!(entry.schema_version, 1);
+    }
+}
@@ -0,0 +1,79 @@
+//! render template files with variable values
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// replace placeholders of template files in app directory
+pub fn render_files(
+    app_dir: &Path,
+    files: &[String],
+    variables: &HashMap<String, String>,
+) -> Result<(), String> {
+    for file in files.iter() {
+        replace_variables(&app_dir.join(file), variables)?;
+    }
+    Ok(())
+}
+
+/// replace placeholders in file and directory names, such as `src/@artifactId@/main.rs`
+pub fn render_paths(app_dir: &Path, variables: &HashMap<String, String>) -> Result<(), String> {
+    let entries = fs::read_dir(app_dir)
+        .map_err(|e| format!("Failed to read {}: {}", app_dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        if path.is_dir() {
+            render_paths(&path, variables)?;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let mut new_name = file_name.clone();
+        for (k, v) in variables.iter() {
+            new_name = new_name.replace(k.as_str(), v.as_str());
+        }
+        if new_name != file_name {
+            let new_path = app_dir.join(&new_name);
+            if let Some(parent) = new_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::rename(&path, &new_path)
+                .map_err(|e| format!("Failed to rename {} to {}: {}", file_name, new_name, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// replace placeholders, such as `@groupId@`, in the file
+pub fn replace_variables(
+    resource_file: &Path,
+    variables: &HashMap<String, String>,
+) -> Result<(), String> {
+    let mut replaced_text = fs::read_to_string(resource_file)
+        .map_err(|e| format!("Failed to read {}: {}", resource_file.display(), e))?;
+    for (k, v) in variables.iter() {
+        replaced_text = replaced_text.replacen(k.as_str(), v.as_str(), 1024);
+    }
+    fs::write(resource_file, replaced_text)
+        .map_err(|e| format!("Failed to write {}: {}", resource_file.display(), e))
+}
+
+/// copy directory recursively, and skip entries with excluded names
+pub fn copy_dir(source_dir: &Path, dest_dir: &Path, excludes: &[&str]) -> io::Result<()> {
+    fs::create_dir_all(dest_dir)?;
+    for entry in fs::read_dir(source_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        if excludes.iter().any(|exclude| file_name == *exclude) {
+            continue;
+        }
+        let dest_path = dest_dir.join(&file_name);
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dest_path, excludes)?;
+        } else {
+            fs::copy(entry.path(), dest_path)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,274 @@
+//! scaffold template from existing project: rewrite literal values into `@name@` placeholders
+use crate::format::{find_file, TEMPLATE_FILE_NAMES};
+use crate::harness::TESTS_DIR;
+use crate::models::{AppTemplate, Variable};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// directories skipped when scanning project
+pub const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules", TESTS_DIR];
+
+/// literal value to be replaced by variable placeholder
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub name: String,
+    pub literal: String,
+}
+
+/// changes to turn project into template
+#[derive(Debug, Default)]
+pub struct ScaffoldPlan {
+    /// relative path of text file and occurrences count of literals
+    pub files: BTreeMap<String, usize>,
+    /// relative paths to rename, deepest path first
+    pub renames: Vec<(String, String)>,
+}
+
+impl Replacement {
+    /// parse `name=value` argument
+    pub fn parse(text: &str) -> Result<Replacement, String> {
+        match text.split_once('=') {
+            Some((name, literal)) if !name.trim().is_empty() && !literal.is_empty() => {
+                Ok(Replacement {
+                    name: name.trim().to_string(),
+                    literal: literal.to_string(),
+                })
+            }
+            _ => Err(format!(
+                "Illegal variable '{}', and format is name=value",
+                text
+            )),
+        }
+    }
+
+    fn placeholder(&self) -> String {
+        format!("@{}@", self.name)
+    }
+}
+
+/// replace literals with placeholders in one pass from left to right, and the longest literal wins at each position,
+/// such as `com.acme.demo` before `demo`. Inserted placeholders are never scanned again.
+fn parameterize(text: &str, replacements: &[Replacement]) -> (String, usize) {
+    let mut replacements: Vec<&Replacement> = replacements.iter().collect();
+    replacements.sort_by_key(|replacement| std::cmp::Reverse(replacement.literal.len()));
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        match replacements
+            .iter()
+            .find(|replacement| rest.starts_with(replacement.literal.as_str()))
+        {
+            Some(replacement) => {
+                result.push_str(&replacement.placeholder());
+                rest = &rest[replacement.literal.len()..];
+                count += 1;
+            }
+            None => {
+                result.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    (result, count)
+}
+
+/// scan project directory to find files and paths containing literals
+pub fn plan(project_dir: &Path, replacements: &[Replacement]) -> Result<ScaffoldPlan, String> {
+    if let Some(template_file) = find_file(project_dir, TEMPLATE_FILE_NAMES) {
+        return Err(format!("{} already exists", template_file.display()));
+    }
+    let mut plan = ScaffoldPlan::default();
+    scan_dir(project_dir, Path::new(""), replacements, &mut plan)
+        .map_err(|e| format!("Failed to scan {}: {}", project_dir.display(), e))?;
+    // rename children before parent directory
+    plan.renames.sort_by(|a, b| {
+        let depth = |path: &str| path.matches('/').count();
+        depth(&b.0).cmp(&depth(&a.0)).then_with(|| a.0.cmp(&b.0))
+    });
+    Ok(plan)
+}
+
+fn scan_dir(
+    base_dir: &Path,
+    relative_dir: &Path,
+    replacements: &[Replacement],
+    plan: &mut ScaffoldPlan,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(base_dir.join(relative_dir))? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let relative_path = relative_dir.join(&file_name);
+        let is_dir = entry.file_type()?.is_dir();
+        if is_dir && SKIPPED_DIRS.contains(&file_name.as_str()) {
+            continue;
+        }
+        let (new_name, count) = parameterize(&file_name, replacements);
+        if count > 0 {
+            plan.renames.push((
+                to_slash(&relative_path),
+                to_slash(&relative_dir.join(new_name)),
+            ));
+        }
+        if is_dir {
+            scan_dir(base_dir, &relative_path, replacements, plan)?;
+        } else if let Ok(text) = fs::read_to_string(entry.path()) {
+            let (_, count) = parameterize(&text, replacements);
+            if count > 0 {
+                plan.files.insert(to_slash(&relative_path), count);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_slash(path: &Path) -> String {
+    let names: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    names.join("/")
+}
+
+/// template path with placeholders of the file
+fn template_path(path: &str, replacements: &[Replacement]) -> String {
+    path.split('/')
+        .map(|name| parameterize(name, replacements).0)
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// rewrite files and rename paths, then generate template.json
+pub fn apply(
+    project_dir: &Path,
+    plan: &ScaffoldPlan,
+    replacements: &[Replacement],
+) -> Result<AppTemplate, String> {
+    for file in plan.files.keys() {
+        let path = project_dir.join(file);
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        fs::write(&path, parameterize(&text, replacements).0)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    for (from, to) in plan.renames.iter() {
+        fs::rename(project_dir.join(from), project_dir.join(to))
+            .map_err(|e| format!("Failed to rename {} to {}: {}", from, to, e))?;
+    }
+    let app_template = AppTemplate {
+        name: project_name(project_dir),
+        repository: git_remote_url(project_dir).unwrap_or_default(),
+        description: format!("{} template", project_name(project_dir)),
+        post_create: None,
+        variables: Some(
+            replacements
+                .iter()
+                .map(|replacement| Variable {
+                    name: replacement.name.clone(),
+                    value: Some(replacement.literal.clone()),
+                    description: replacement.name.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        files: Some(
+            plan.files
+                .keys()
+                .map(|file| template_path(file, replacements))
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    let json_text = serde_json::to_string_pretty(&app_template).unwrap();
+    fs::write(project_dir.join("template.json"), json_text + "\n")
+        .map_err(|e| format!("Failed to write template.json: {}", e))?;
+    Ok(app_template)
+}
+
+fn project_name(project_dir: &Path) -> String {
+    project_dir
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(project_dir))
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn git_remote_url(project_dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .current_dir(project_dir)
+        .output()
+        .ok()?;
+    let url = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if output.status.success() && !url.is_empty() {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaffold() {
+        let project_dir = std::env::temp_dir().join(format!("tgm-scaffold-{}", std::process::id()));
+        let package_dir = project_dir.join("src/main/java/com/acme/demo");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::create_dir_all(project_dir.join("target")).unwrap();
+        fs::write(
+            project_dir.join("pom.xml"),
+            "<groupId>com.acme.demo</groupId>\n<artifactId>demo</artifactId>\n",
+        )
+        .unwrap();
+        fs::write(package_dir.join("DemoApp.java"), "package com.acme.demo;\n").unwrap();
+        fs::write(project_dir.join("target/demo.jar"), "demo").unwrap();
+        let replacements = vec![
+            Replacement::parse("artifactId=demo").unwrap(),
+            Replacement::parse("groupId=com.acme.demo").unwrap(),
+        ];
+        assert!(Replacement::parse("groupId").is_err());
+        // placeholders inserted are never matched by later literals
+        let title_replacements = vec![
+            Replacement::parse("appName=My App").unwrap(),
+            Replacement::parse("name=app").unwrap(),
+        ];
+        assert_eq!(
+            parameterize("title: My App\nid: app\n", &title_replacements),
+            ("title: @appName@\nid: @name@\n".to_string(), 2)
+        );
+        let plan = plan(&project_dir, &replacements).unwrap();
+        assert_eq!(plan.files.get("pom.xml"), Some(&2));
+        assert!(!plan.files.contains_key("target/demo.jar"));
+        assert_eq!(
+            plan.renames,
+            vec![(
+                "src/main/java/com/acme/demo".to_string(),
+                "src/main/java/com/acme/@artifactId@".to_string()
+            )]
+        );
+        let app_template = apply(&project_dir, &plan, &replacements).unwrap();
+        let pom_xml = fs::read_to_string(project_dir.join("pom.xml")).unwrap();
+        let java_file = project_dir.join("src/main/java/com/acme/@artifactId@/DemoApp.java");
+        let java_text = fs::read_to_string(java_file).unwrap_or_default();
+        let template_json_exists = project_dir.join("template.json").exists();
+        fs::remove_dir_all(&project_dir).unwrap();
+        assert_eq!(
+            pom_xml,
+            "<groupId>@groupId@</groupId>\n<artifactId>@artifactId@</artifactId>\n"
+        );
+        assert_eq!(java_text, "package @groupId@;\n");
+        assert!(template_json_exists);
+        assert_eq!(
+            app_template.files.unwrap(),
+            vec![
+                "pom.xml",
+                "src/main/java/com/acme/@artifactId@/DemoApp.java"
+            ]
+        );
+    }
+}
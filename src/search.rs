@@ -0,0 +1,119 @@
+//! template search with tag filters and ranked fuzzy matching
+use crate::models::Template;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::Serialize;
+
+/// template with its source, such as local, remote or registry url
+#[derive(Serialize)]
+pub struct Candidate {
+    pub source: String,
+    #[serde(flatten)]
+    pub template: Template,
+}
+
+/// matched candidate with score, and higher score is better
+pub struct SearchHit<'a> {
+    pub score: i64,
+    pub candidate: &'a Candidate,
+}
+
+/// search candidates by query and tags: all tags must be present, and query is fuzzy matched
+/// with name, description and tags. Name matches weigh more than description and tag matches.
+pub fn search<'a>(candidates: &'a [Candidate], query: &str, tags: &[String]) -> Vec<SearchHit<'a>> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let query = query.trim();
+    let mut hits: Vec<SearchHit> = candidates
+        .iter()
+        .filter(|candidate| has_tags(&candidate.template, tags))
+        .filter_map(|candidate| {
+            let score = if query.is_empty() {
+                Some(0)
+            } else {
+                match_score(&matcher, &candidate.template, query)
+            };
+            score.map(|score| SearchHit { score, candidate })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.candidate.template.name.cmp(&b.candidate.template.name))
+    });
+    hits
+}
+
+fn has_tags(template: &Template, tags: &[String]) -> bool {
+    tags.iter().all(|tag| {
+        template
+            .tags
+            .iter()
+            .any(|template_tag| template_tag.eq_ignore_ascii_case(tag))
+    })
+}
+
+fn match_score(matcher: &SkimMatcherV2, template: &Template, query: &str) -> Option<i64> {
+    let mut scores = vec![];
+    if template.name.eq_ignore_ascii_case(query) {
+        scores.push(i64::MAX / 2);
+    }
+    if let Some(score) = matcher.fuzzy_match(&template.name, query) {
+        scores.push(score * 3);
+    }
+    for tag in template.tags.iter() {
+        if tag.eq_ignore_ascii_case(query) {
+            scores.push(200);
+        } else if let Some(score) = matcher.fuzzy_match(tag, query) {
+            scores.push(score * 2);
+        }
+    }
+    if let Some(score) = matcher.fuzzy_match(&template.description, query) {
+        scores.push(score);
+    }
+    scores.into_iter().max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, description: &str, tags: &[&str]) -> Candidate {
+        Candidate {
+            source: "local".to_string(),
+            template: Template {
+                name: name.to_string(),
+                repository: format!("https://github.com/tgm-templates/{}", name),
+                description: description.to_string(),
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let candidates = vec![
+            candidate(
+                "spring-boot-java",
+                "Spring Boot App with Java",
+                &["java", "spring"],
+            ),
+            candidate(
+                "spring-boot-kotlin",
+                "Spring Boot App with Kotlin",
+                &["kotlin", "spring"],
+            ),
+            candidate("vue-app", "Vue app with spring style", &["javascript"]),
+        ];
+        let hits = search(&candidates, "spring", &[]);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[2].candidate.template.name, "vue-app");
+        let hits = search(&candidates, "kotlin", &[]);
+        assert_eq!(hits[0].candidate.template.name, "spring-boot-kotlin");
+        let tags = vec!["Spring".to_string(), "java".to_string()];
+        let hits = search(&candidates, "", &tags);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].candidate.template.name, "spring-boot-java");
+        assert!(search(&candidates, "rust", &[]).is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+//! secret references in global variables: `env:NAME`, `file:path` and `cmd:command`
+use crate::paths;
+use std::env;
+use std::fs;
+use std::process::Command;
+
+/// mask of secret value in output
+pub const MASK: &str = "******";
+
+/// whether the value is a secret reference
+pub fn is_reference(value: &str) -> bool {
+    ["env:", "file:", "cmd:"]
+        .iter()
+        .any(|prefix| value.starts_with(prefix))
+}
+
+/// resolve reference to value, and plain value is returned as it is.
+/// Only references from secret global variables should be resolved, see [crate::models::Settings::resolve_variable_value],
+/// and template's defaults are never resolved.
+pub fn resolve(value: &str) -> Result<String, String> {
+    if let Some(name) = value.strip_prefix("env:") {
+        env::var(name).map_err(|_| format!("Environment variable {} not found", name))
+    } else if let Some(path) = value.strip_prefix("file:") {
+        let path = match path.strip_prefix("~/") {
+            Some(relative_path) => paths::home_dir()
+                .map(|home| home.join(relative_path))
+                .ok_or_else(|| String::from("Home directory not found"))?,
+            None => path.into(),
+        };
+        fs::read_to_string(&path)
+            .map(|text| text.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    } else if let Some(command) = value.strip_prefix("cmd:") {
+        let output = if cfg!(windows) {
+            Command::new("cmd").args(["/C", command]).output()
+        } else {
+            Command::new("sh").args(["-c", command]).output()
+        }
+        .map_err(|e| format!("Failed to execute '{}': {}", command, e))?;
+        if !output.status.success() {
+            return Err(format!("'{}' failed with {}", command, output.status));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches(['\r', '\n'])
+            .to_string())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve("plain").unwrap(), "plain");
+        assert_eq!(resolve("cmd:echo s3cr3t").unwrap(), "s3cr3t");
+        assert!(resolve("env:TGM_ABSENT_SECRET").is_err());
+        let secret_file = env::temp_dir().join(format!("tgm-secret-{}", std::process::id()));
+        fs::write(&secret_file, "token\n").unwrap();
+        let value = resolve(&format!("file:{}", secret_file.display()));
+        fs::remove_file(&secret_file).unwrap();
+        assert_eq!(value.unwrap(), "token");
+        assert!(is_reference("env:NPM_TOKEN"));
+    }
+}
@@ -0,0 +1,344 @@
+//! template source resolution: local template.json, GitHub/GitLab/Gitea repository urls and raw urls
+use crate::format::{find_file, is_template_file, TEMPLATE_FILE_NAMES};
+use crate::github::GithubApi;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// default organization for short template names
+pub const DEFAULT_ORG: &str = "tgm-templates";
+
+/// git hosting service
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GitHost {
+    Github,
+    Gitlab,
+    Gitea,
+}
+
+/// template source
+#[derive(Debug, PartialEq)]
+pub enum TemplateSource {
+    /// local template.json file
+    Local(PathBuf),
+    /// template in git repository
+    Repository(RepoSource),
+    /// raw url of template.json
+    Url(String),
+}
+
+/// git repository with optional ref and sub directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoSource {
+    pub host: GitHost,
+    /// web base url, such as https://github.com
+    pub base_url: String,
+    /// repository path, such as owner/repo or group/sub-group/repo
+    pub path: String,
+    pub git_ref: Option<String>,
+    pub subdir: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RepoInfo {
+    default_branch: Option<String>,
+}
+
+impl TemplateSource {
+    /// parse `import` argument: template name, `owner/repo`, web url or local path, with optional `@ref` suffix
+    pub fn parse(input: &str) -> Result<TemplateSource, String> {
+        let input = input.trim();
+        let path = Path::new(input);
+        if path.is_dir() {
+            let template_file =
+                find_file(path, TEMPLATE_FILE_NAMES).unwrap_or_else(|| path.join("template.json"));
+            return Ok(TemplateSource::Local(template_file));
+        } else if path.is_file() {
+            return Ok(TemplateSource::Local(path.to_path_buf()));
+        }
+        if input.starts_with("http://") || input.starts_with("https://") {
+            return parse_url(input);
+        }
+        // ssh url, such as git@github.com:owner/repo.git
+        if let Some((host, repo)) = input
+            .strip_prefix("git@")
+            .and_then(|ssh| ssh.split_once(':'))
+        {
+            return parse_url(&format!("https://{}/{}", host, repo));
+        }
+        let is_template_path = [".json", ".yaml", ".yml", ".toml"]
+            .iter()
+            .any(|ext| input.ends_with(ext));
+        if is_template_path || input.starts_with('.') || input.starts_with('/') {
+            return Err(format!("File not found: {}", input));
+        }
+        // GitHub repository: name, owner/repo
+        let (repo, git_ref) = split_ref(input);
+        let repo = repo.trim_matches('/');
+        if repo.is_empty() || repo.split('/').count() > 2 {
+            return Err(format!("Illegal repository: {}", input));
+        }
+        let repo_path = if repo.contains('/') {
+            repo.to_string()
+        } else {
+            format!("{}/{}", DEFAULT_ORG, repo)
+        };
+        Ok(TemplateSource::Repository(RepoSource {
+            host: GitHost::Github,
+            base_url: String::from("https://github.com"),
+            path: repo_path,
+            git_ref,
+            subdir: None,
+        }))
+    }
+}
+
+impl RepoSource {
+    /// web url of the repository
+    pub fn repository_url(&self) -> String {
+        format!("{}/{}", self.base_url, self.path)
+    }
+
+    /// raw url of file in the repository with the ref
+    pub fn raw_url(&self, git_ref: &str, file_name: &str) -> String {
+        let file_path = match &self.subdir {
+            Some(subdir) => format!("{}/{}", subdir, file_name),
+            None => file_name.to_string(),
+        };
+        match self.host {
+            GitHost::Github if self.base_url == "https://github.com" => format!(
+                "https://raw.githubusercontent.com/{}/{}/{}",
+                self.path, git_ref, file_path
+            ),
+            // GitHub Enterprise and Gitea
+            GitHost::Github | GitHost::Gitea => format!(
+                "{}/{}/raw/{}/{}",
+                self.base_url, self.path, git_ref, file_path
+            ),
+            GitHost::Gitlab => format!(
+                "{}/{}/-/raw/{}/{}",
+                self.base_url, self.path, git_ref, file_path
+            ),
+        }
+    }
+
+    /// ref to fetch files: explicit ref, default branch from API, or HEAD
+    pub fn resolve_ref(&self, github_api: &GithubApi) -> String {
+        if let Some(git_ref) = &self.git_ref {
+            return git_ref.clone();
+        }
+        self.default_branch(github_api)
+            .unwrap_or_else(|| String::from("HEAD"))
+    }
+
+    /// detect default branch by hosting service API
+    pub fn default_branch(&self, github_api: &GithubApi) -> Option<String> {
+        let repo_info: RepoInfo = match self.host {
+            GitHost::Github => github_api.get(&format!("/repos/{}", self.path)).ok()?,
+            GitHost::Gitlab => {
+                let url = format!(
+                    "{}/api/v4/projects/{}",
+                    self.base_url,
+                    self.path.replace('/', "%2F")
+                );
+                reqwest::blocking::get(url).ok()?.json().ok()?
+            }
+            GitHost::Gitea => {
+                let url = format!("{}/api/v1/repos/{}", self.base_url, self.path);
+                reqwest::blocking::get(url).ok()?.json().ok()?
+            }
+        };
+        repo_info.default_branch
+    }
+}
+
+/// split `repo@ref` into repo and ref
+fn split_ref(text: &str) -> (&str, Option<String>) {
+    match text.rsplit_once('@') {
+        Some((repo, git_ref)) if !git_ref.is_empty() && !git_ref.contains('/') => {
+            (repo, Some(git_ref.to_string()))
+        }
+        _ => (text, None),
+    }
+}
+
+fn parse_url(url: &str) -> Result<TemplateSource, String> {
+    let (scheme, rest) = url.split_once("://").unwrap();
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (path, at_ref) = split_ref(path);
+    let segments: Vec<&str> = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let base_url = format!("{}://{}", scheme, host);
+    // raw.githubusercontent.com/{owner}/{repo}/{ref}/{subdir}/template.json
+    if host == "raw.githubusercontent.com" {
+        if segments.len() < 4 || !is_template_file(segments.last().unwrap()) {
+            return Ok(TemplateSource::Url(url.to_string()));
+        }
+        return Ok(repo_source(
+            GitHost::Github,
+            "https://github.com".to_string(),
+            &segments[..2],
+            Some(segments[2].to_string()),
+            &segments[3..segments.len() - 1],
+        ));
+    }
+    let host_type = if host == "github.com" || host.starts_with("github.") {
+        GitHost::Github
+    } else if host.contains("gitlab") {
+        GitHost::Gitlab
+    } else {
+        GitHost::Gitea
+    };
+    let (repo_segments, git_ref, subdir_segments) = match host_type {
+        GitHost::Gitlab => {
+            // {group}/{sub-group}/{repo}/-/{tree|blob|raw}/{ref}/{subdir}
+            match segments.iter().position(|segment| *segment == "-") {
+                Some(pos) if segments.len() > pos + 2 => (
+                    &segments[..pos],
+                    Some(segments[pos + 2].to_string()),
+                    &segments[pos + 3..],
+                ),
+                Some(pos) => (&segments[..pos], None, &segments[0..0]),
+                None => (&segments[..], None, &segments[0..0]),
+            }
+        }
+        GitHost::Github => {
+            // {owner}/{repo}/{tree|blob}/{ref}/{subdir}
+            if segments.len() > 3 && ["tree", "blob"].contains(&segments[2]) {
+                (
+                    &segments[..2],
+                    Some(segments[3].to_string()),
+                    &segments[4..],
+                )
+            } else {
+                (&segments[..segments.len().min(2)], None, &segments[0..0])
+            }
+        }
+        GitHost::Gitea => {
+            // {owner}/{repo}/src/{branch|tag|commit}/{ref}/{subdir}
+            if segments.len() > 4 && segments[2] == "src" {
+                (
+                    &segments[..2],
+                    Some(segments[4].to_string()),
+                    &segments[5..],
+                )
+            } else if segments.last().is_some_and(|name| is_template_file(name)) {
+                return Ok(TemplateSource::Url(url.to_string()));
+            } else {
+                (&segments[..segments.len().min(2)], None, &segments[0..0])
+            }
+        }
+    };
+    if repo_segments.len() < 2 {
+        return Err(format!("Illegal repository url: {}", url));
+    }
+    let mut subdir_segments = subdir_segments;
+    if subdir_segments
+        .last()
+        .is_some_and(|name| is_template_file(name))
+    {
+        subdir_segments = &subdir_segments[..subdir_segments.len() - 1];
+    }
+    Ok(repo_source(
+        host_type,
+        base_url,
+        repo_segments,
+        git_ref.or(at_ref),
+        subdir_segments,
+    ))
+}
+
+fn repo_source(
+    host: GitHost,
+    base_url: String,
+    repo_segments: &[&str],
+    git_ref: Option<String>,
+    subdir_segments: &[&str],
+) -> TemplateSource {
+    let path = repo_segments.join("/");
+    TemplateSource::Repository(RepoSource {
+        host,
+        base_url,
+        path: path.trim_end_matches(".git").to_string(),
+        git_ref,
+        subdir: if subdir_segments.is_empty() {
+            None
+        } else {
+            Some(subdir_segments.join("/"))
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(input: &str) -> RepoSource {
+        match TemplateSource::parse(input).unwrap() {
+            TemplateSource::Repository(repo) => repo,
+            source => panic!("not repository: {:?}", source),
+        }
+    }
+
+    #[test]
+    fn test_parse_github() {
+        let source = repo("spring-boot-java");
+        assert_eq!(
+            source.repository_url(),
+            "https://github.com/tgm-templates/spring-boot-java"
+        );
+        let source = repo("linux-china/spring-boot-java-template@v1.0");
+        assert_eq!(source.path, "linux-china/spring-boot-java-template");
+        assert_eq!(source.git_ref.as_deref(), Some("v1.0"));
+        let source = repo("https://github.com/linux-china/spring-boot-java-template.git");
+        assert_eq!(source.path, "linux-china/spring-boot-java-template");
+        assert!(source.git_ref.is_none());
+        let source = repo("git@github.com:linux-china/spring-boot-java-template.git");
+        assert_eq!(source.path, "linux-china/spring-boot-java-template");
+        let source = repo("https://github.com/acme/templates/tree/main/java/spring");
+        assert_eq!(source.git_ref.as_deref(), Some("main"));
+        assert_eq!(source.subdir.as_deref(), Some("java/spring"));
+        assert_eq!(
+            source.raw_url("main", "template.json"),
+            "https://raw.githubusercontent.com/acme/templates/main/java/spring/template.json"
+        );
+        let source = repo("https://raw.githubusercontent.com/acme/demo/master/template.json");
+        assert_eq!(source.git_ref.as_deref(), Some("master"));
+        assert!(source.subdir.is_none());
+    }
+
+    #[test]
+    fn test_parse_gitlab_and_gitea() {
+        let source = repo("https://gitlab.com/acme/java/spring-template/-/tree/develop/app");
+        assert_eq!(source.host, GitHost::Gitlab);
+        assert_eq!(source.path, "acme/java/spring-template");
+        assert_eq!(
+            source.raw_url("develop", "template.json"),
+            "https://gitlab.com/acme/java/spring-template/-/raw/develop/app/template.json"
+        );
+        let source = repo("https://gitlab.example.com/acme/demo@v2");
+        assert_eq!(source.git_ref.as_deref(), Some("v2"));
+        let source = repo("https://git.example.com/acme/demo/src/branch/main");
+        assert_eq!(source.host, GitHost::Gitea);
+        assert_eq!(source.git_ref.as_deref(), Some("main"));
+        assert_eq!(
+            source.raw_url("main", "template.json"),
+            "https://git.example.com/acme/demo/raw/main/template.json"
+        );
+    }
+
+    #[test]
+    fn test_parse_local_and_url() {
+        assert_eq!(
+            TemplateSource::parse("tgm_data").unwrap(),
+            TemplateSource::Local(PathBuf::from("tgm_data/template.json"))
+        );
+        assert!(TemplateSource::parse("./absent/template.json").is_err());
+        assert_eq!(
+            TemplateSource::parse("https://example.com/demo/template.json").unwrap(),
+            TemplateSource::Url(String::from("https://example.com/demo/template.json"))
+        );
+    }
+}
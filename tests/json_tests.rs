@@ -1,7 +1,6 @@
 use chrono::{DateTime, Datelike, Local};
 use serde::{Deserialize, Serialize};
 use serde_json::Result;
-use std::env;
 use std::fs;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -20,8 +19,8 @@ pub struct Contact {
 
 #[test]
 fn test_path() {
-    let home = env::var("HOME").unwrap();
-    let file_name = home + "/.tgm/settings.json";
+    tgm::paths::use_test_home();
+    let file_name = tgm::paths::settings_file();
     let contents = fs::read_to_string(file_name).expect("Something went wrong reading the file");
     println!("{}", contents);
 }
@@ -51,8 +50,5 @@ fn test_json() -> Result<()> {
 fn test_chrono() {
     let now: DateTime<Local> = Local::now();
     println!("{}", now.year());
-    println!(
-        "{}",
-        format!("{}/{}/{}", now.month(), now.day(), now.year())
-    );
+    println!("{}/{}/{}", now.month(), now.day(), now.year());
 }